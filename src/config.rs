@@ -0,0 +1,467 @@
+//! Settings for the CLI and core, read from ~/.seymour/config.toml.
+//!
+//! Every setting has a default. Settings in the file override the defaults,
+//! environment variables override the file, and command line flags override
+//! both. Each value remembers where it came from, so that the effective config
+//! can be explained.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::Deserialize;
+
+use crate::{validate_retention, Error, RetentionPolicy, SyncOptions, MAX_RETRY_DELAY};
+
+/// The most retries a sync may make of a single fetch.
+const MAX_RETRIES: usize = 10;
+
+/// Config holds every setting along with where it came from.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The config file settings were read from, if one was found.
+    pub file: Option<PathBuf>,
+    /// The database file, or None for Store::default_path.
+    pub db: Setting<Option<PathBuf>>,
+    /// Sent with every fetch.
+    pub user_agent: Setting<String>,
+    /// How long a fetch waits to connect to the server.
+    pub connect_timeout: Setting<Duration>,
+    /// How long a fetch may take altogether, body included.
+    pub timeout: Setting<Duration>,
+    pub max_concurrent: Setting<usize>,
+    pub max_per_host: Setting<usize>,
+    pub max_retries: Setting<usize>,
+    pub retry_delay: Setting<Duration>,
+    /// A global retention policy to use instead of the one stored in the
+    /// database.
+    pub retention: Setting<Option<RetentionPolicy>>,
+    /// How many entries a page of the timeline lists.
+    pub timeline_limit: Setting<usize>,
+    /// How timestamps are printed, in strftime syntax.
+    pub date_format: Setting<String>,
+    pub time_zone: Setting<TimeZone>,
+    /// The command long output is paged through, or None to not page it.
+    pub pager: Setting<Option<String>>,
+}
+
+/// Setting is a config value and where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn default(value: T) -> Self {
+        Self { value, source: Source::Default }
+    }
+
+    /// Replaces the value, recording where the new one came from.
+    pub fn set(&mut self, value: T, source: Source) {
+        *self = Self { value, source };
+    }
+}
+
+/// Source is where a setting's value came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    /// An environment variable, by name.
+    Env(&'static str),
+    /// A command line flag, by name.
+    Flag(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Env(name) => write!(f, "${name}"),
+            Source::Flag(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// TimeZone is the time zone timestamps are printed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeZone {
+    Utc,
+    /// The time zone of the machine.
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl TimeZone {
+    /// Formats a unix timestamp in this time zone. The format must have passed
+    /// validation, or formatting panics.
+    pub fn format(&self, ts: u64, format: &str) -> String {
+        let Some(utc) = DateTime::<Utc>::from_timestamp(ts as i64, 0) else {
+            return ts.to_string();
+        };
+        match self {
+            TimeZone::Utc => utc.format(format).to_string(),
+            TimeZone::Local => utc.with_timezone(&Local).format(format).to_string(),
+            TimeZone::Fixed(offset) => utc.with_timezone(offset).format(format).to_string(),
+        }
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            _ if s.eq_ignore_ascii_case("utc") => Ok(TimeZone::Utc),
+            _ if s.eq_ignore_ascii_case("local") => Ok(TimeZone::Local),
+            _ => s.parse::<FixedOffset>().map(TimeZone::Fixed).map_err(|_| {
+                Error::InvalidArgument(format!(
+                    "time_zone must be UTC, local or an offset like +02:00, not {s:?}"
+                ))
+            }),
+        }
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeZone::Utc => write!(f, "UTC"),
+            TimeZone::Local => write!(f, "local"),
+            TimeZone::Fixed(offset) => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let sync = SyncOptions::default();
+        Self {
+            file: None,
+            db: Setting::default(None),
+            user_agent: Setting::default(concat!("seymour/", env!("CARGO_PKG_VERSION")).to_string()),
+            connect_timeout: Setting::default(Duration::from_secs(10)),
+            timeout: Setting::default(Duration::from_secs(30)),
+            max_concurrent: Setting::default(sync.max_concurrent),
+            max_per_host: Setting::default(sync.max_per_host),
+            max_retries: Setting::default(sync.max_retries),
+            retry_delay: Setting::default(sync.retry_delay),
+            retention: Setting::default(None),
+            timeline_limit: Setting::default(100),
+            date_format: Setting::default("%Y-%m-%d %H:%M:%S".to_string()),
+            time_zone: Setting::default(TimeZone::Utc),
+            pager: Setting::default(Some("less".to_string())),
+        }
+    }
+}
+
+/// The layout of config.toml. Every key is optional; unknown keys are errors,
+/// so that typos don't go unnoticed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    db: Option<String>,
+    user_agent: Option<String>,
+    timeline_limit: Option<usize>,
+    date_format: Option<String>,
+    time_zone: Option<String>,
+    /// An empty pager turns paging off.
+    pager: Option<String>,
+    #[serde(default)]
+    http: HttpSection,
+    #[serde(default)]
+    sync: SyncSection,
+    retention: Option<RetentionSection>,
+}
+
+/// Timeouts are in seconds.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HttpSection {
+    connect_timeout: Option<u64>,
+    timeout: Option<u64>,
+}
+
+/// The retry delay is in seconds.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SyncSection {
+    max_concurrent: Option<usize>,
+    max_per_host: Option<usize>,
+    max_retries: Option<usize>,
+    retry_delay: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetentionSection {
+    max_age_days: Option<u64>,
+    max_entries: Option<u64>,
+    #[serde(default)]
+    keep_unread: bool,
+}
+
+impl Config {
+    /// Where the config file is looked for when no other path is given:
+    /// $HOME/.seymour/config.toml.
+    pub fn default_path() -> Result<PathBuf, Error> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| Error::Internal("could not determine home directory".into()))?;
+        Ok(home.join(".seymour").join("config.toml"))
+    }
+
+    /// Loads the config file at path, or at the default path when None, then
+    /// applies the environment. A missing file is only an error when its path
+    /// was given.
+    pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => (Self::default_path()?, false),
+        };
+        let mut config = Self::default();
+        match fs::read_to_string(&path) {
+            Ok(text) => config.apply_file(&text, &path)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && !required => {}
+            Err(err) => {
+                return Err(Error::InvalidArgument(format!("reading {}: {err}", path.display())))
+            }
+        }
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Applies the settings in a config file. path is only used to tell where
+    /// the settings came from, and in errors.
+    pub fn apply_file(&mut self, text: &str, path: &Path) -> Result<(), Error> {
+        let invalid = |msg: String| Error::InvalidArgument(format!("{}: {msg}", path.display()));
+        let file: File = toml::from_str(text).map_err(|err| invalid(err.to_string()))?;
+        let source = Source::File(path.to_path_buf());
+        self.file = Some(path.to_path_buf());
+
+        if let Some(db) = file.db {
+            self.db.set(Some(expand_home(&db)), source.clone());
+        }
+        if let Some(user_agent) = file.user_agent {
+            self.user_agent.set(validate_user_agent(user_agent).map_err(invalid)?, source.clone());
+        }
+        if let Some(limit) = file.timeline_limit {
+            self.timeline_limit.set(at_least_one("timeline_limit", limit).map_err(invalid)?, source.clone());
+        }
+        if let Some(format) = file.date_format {
+            self.date_format.set(validate_date_format(format).map_err(invalid)?, source.clone());
+        }
+        if let Some(time_zone) = file.time_zone {
+            let time_zone = time_zone.trim().parse().map_err(|err: Error| invalid(message(err)))?;
+            self.time_zone.set(time_zone, source.clone());
+        }
+        if let Some(pager) = file.pager {
+            self.pager.set(non_empty(pager), source.clone());
+        }
+
+        if let Some(seconds) = file.http.connect_timeout {
+            let seconds = at_least_one("http.connect_timeout", seconds).map_err(invalid)?;
+            self.connect_timeout.set(Duration::from_secs(seconds), source.clone());
+        }
+        if let Some(seconds) = file.http.timeout {
+            let seconds = at_least_one("http.timeout", seconds).map_err(invalid)?;
+            self.timeout.set(Duration::from_secs(seconds), source.clone());
+        }
+
+        if let Some(max) = file.sync.max_concurrent {
+            self.max_concurrent.set(at_least_one("sync.max_concurrent", max).map_err(invalid)?, source.clone());
+        }
+        if let Some(max) = file.sync.max_per_host {
+            self.max_per_host.set(at_least_one("sync.max_per_host", max).map_err(invalid)?, source.clone());
+        }
+        if let Some(max) = file.sync.max_retries {
+            self.max_retries.set(at_most("sync.max_retries", max, MAX_RETRIES).map_err(invalid)?, source.clone());
+        }
+        if let Some(seconds) = file.sync.retry_delay {
+            let seconds = at_most("sync.retry_delay", seconds, MAX_RETRY_DELAY.as_secs()).map_err(invalid)?;
+            self.retry_delay.set(Duration::from_secs(seconds), source.clone());
+        }
+
+        if let Some(retention) = file.retention {
+            let policy = RetentionPolicy {
+                max_age_days: retention.max_age_days,
+                max_entries: retention.max_entries,
+                keep_unread: retention.keep_unread,
+            };
+            validate_retention(&policy).map_err(|err| invalid(format!("retention: {}", message(err))))?;
+            self.retention.set(Some(policy), source);
+        }
+        Ok(())
+    }
+
+    /// Applies the environment variables that override the file: SEYMOUR_DB
+    /// and PAGER. var looks a variable up by name.
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), Error> {
+        if let Some(db) = var("SEYMOUR_DB").filter(|db| !db.is_empty()) {
+            self.db.set(Some(expand_home(&db)), Source::Env("SEYMOUR_DB"));
+        }
+        if let Some(pager) = var("PAGER") {
+            self.pager.set(non_empty(pager), Source::Env("PAGER"));
+        }
+        Ok(())
+    }
+
+    /// The sync options the config describes.
+    pub fn sync_options(&self) -> SyncOptions {
+        SyncOptions {
+            max_concurrent: self.max_concurrent.value,
+            max_per_host: self.max_per_host.value,
+            max_retries: self.max_retries.value,
+            retry_delay: self.retry_delay.value,
+        }
+    }
+
+    /// Formats a unix timestamp with the configured format and time zone.
+    pub fn format_timestamp(&self, ts: u64) -> String {
+        self.time_zone.value.format(ts, &self.date_format.value)
+    }
+}
+
+/// Expands a leading ~ to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// The message of an InvalidArgument, without the "invalid argument" prefix.
+fn message(err: Error) -> String {
+    match err {
+        Error::InvalidArgument(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+fn at_least_one<T: PartialOrd + From<u8>>(key: &str, value: T) -> Result<T, String> {
+    if value < T::from(1) {
+        return Err(format!("{key} must be at least 1"));
+    }
+    Ok(value)
+}
+
+fn at_most<T: PartialOrd + fmt::Display>(key: &str, value: T, max: T) -> Result<T, String> {
+    if value > max {
+        return Err(format!("{key} must be at most {max}"));
+    }
+    Ok(value)
+}
+
+fn validate_user_agent(user_agent: String) -> Result<String, String> {
+    let user_agent = user_agent.trim().to_string();
+    if user_agent.is_empty() || reqwest::header::HeaderValue::from_str(&user_agent).is_err() {
+        return Err(format!("user_agent {user_agent:?} can't be sent as a header"));
+    }
+    Ok(user_agent)
+}
+
+fn validate_date_format(format: String) -> Result<String, String> {
+    if format.is_empty() {
+        return Err("date_format is empty".to_string());
+    }
+    if StrftimeItems::new(&format).parse().is_err() {
+        return Err(format!("date_format {format:?} isn't a valid strftime format"));
+    }
+    Ok(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Config, Error> {
+        let mut config = Config::default();
+        config.apply_file(text, Path::new("config.toml"))?;
+        Ok(config)
+    }
+
+    #[test]
+    fn file_overrides_defaults_and_env_overrides_file() {
+        let mut config = parse(
+            r#"
+            db = "/var/lib/seymour.sqlite3"
+            user_agent = "my-reader/1.0"
+            timeline_limit = 25
+            date_format = "%d.%m.%Y %H:%M"
+            time_zone = "+02:00"
+            pager = ""
+
+            [http]
+            timeout = 60
+
+            [sync]
+            max_per_host = 1
+            retry_delay = 0
+
+            [retention]
+            max_age_days = 30
+            keep_unread = true
+            "#,
+        )
+        .unwrap();
+
+        let file = Source::File(PathBuf::from("config.toml"));
+        assert_eq!(config.db, Setting { value: Some(PathBuf::from("/var/lib/seymour.sqlite3")), source: file.clone() });
+        assert_eq!(config.timeout, Setting { value: Duration::from_secs(60), source: file.clone() });
+        assert_eq!(config.connect_timeout.source, Source::Default);
+        assert_eq!(config.sync_options().max_per_host, 1);
+        assert_eq!(config.sync_options().max_concurrent, 8);
+        assert_eq!(config.pager.value, None);
+        assert_eq!(
+            config.retention.value,
+            Some(RetentionPolicy { max_age_days: Some(30), max_entries: None, keep_unread: true })
+        );
+        // 2026-01-10 12:00:00 UTC
+        assert_eq!(config.format_timestamp(1768046400), "10.01.2026 14:00");
+
+        config
+            .apply_env(|name| (name == "SEYMOUR_DB").then(|| "/tmp/other.sqlite3".to_string()))
+            .unwrap();
+        assert_eq!(config.db.source, Source::Env("SEYMOUR_DB"));
+        assert_eq!(config.user_agent.source, file);
+    }
+
+    #[test]
+    fn invalid_settings_say_what_is_wrong() {
+        let error = |text: &str| match parse(text) {
+            Err(Error::InvalidArgument(msg)) => msg,
+            other => panic!("expected an invalid argument, got {:?}", other.map(|_| ())),
+        };
+
+        assert_eq!(error("[sync]\nmax_concurrent = 0"), "config.toml: sync.max_concurrent must be at least 1");
+        assert_eq!(error("[sync]\nmax_retries = 11"), "config.toml: sync.max_retries must be at most 10");
+        assert_eq!(error("[sync]\nretry_delay = 1000000"), "config.toml: sync.retry_delay must be at most 60");
+        assert_eq!(
+            error("time_zone = \"Mars/Olympus\""),
+            "config.toml: time_zone must be UTC, local or an offset like +02:00, not \"Mars/Olympus\""
+        );
+        assert_eq!(
+            error("date_format = \"%Q\""),
+            "config.toml: date_format \"%Q\" isn't a valid strftime format"
+        );
+        assert_eq!(error("[retention]\nmax_entries = 0"), "config.toml: retention: max entries must be at least 1");
+        assert!(error("timeline_limt = 5").contains("unknown field `timeline_limt`"));
+        assert!(error("timeline_limit = \"five\"").contains("invalid type"));
+    }
+
+    #[test]
+    fn named_config_files_must_exist() {
+        let path = std::env::temp_dir().join(format!("seymour-{}.toml", uuid::Uuid::new_v4()));
+        assert!(matches!(Config::load(Some(&path)), Err(Error::InvalidArgument(_))));
+    }
+}
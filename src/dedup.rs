@@ -0,0 +1,72 @@
+//! Spotting the same post in more than one feed, e.g. in a blog's main feed and
+//! in one of its category feeds, which give it different GUIDs.
+//!
+//! Entries are compared by keys normalized from their link and title. An entry
+//! is a duplicate of an earlier one from another feed that has the same link
+//! key, or the same title key and was published within TITLE_WINDOW of it.
+
+/// How far apart, in seconds, two entries with the same title can have been
+/// published and still be taken for the same post.
+pub const TITLE_WINDOW: u64 = 2 * 24 * 60 * 60;
+
+/// Normalizes a link for comparison. The scheme, a leading `www.`, the fragment
+/// and trailing slashes don't tell posts apart. Returns None for empty links.
+pub fn link_key(link: &str) -> Option<String> {
+    let link = link.trim();
+    if link.is_empty() {
+        return None;
+    }
+    let Ok(url) = reqwest::Url::parse(link) else {
+        return Some(link.to_string());
+    };
+    let Some(host) = url.host_str() else {
+        return Some(link.to_string());
+    };
+
+    let mut key = host.strip_prefix("www.").unwrap_or(host).to_string();
+    if let Some(port) = url.port() {
+        key.push_str(&format!(":{port}"));
+    }
+    key.push_str(url.path().trim_end_matches('/'));
+    if let Some(query) = url.query().filter(|q| !q.is_empty()) {
+        key.push('?');
+        key.push_str(query);
+    }
+    Some(key)
+}
+
+/// Normalizes a title for comparison. Case, punctuation and whitespace are
+/// ignored. Returns None for titles with no words in them.
+pub fn title_key(title: &str) -> Option<String> {
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_keys_ignore_scheme_www_fragment_and_trailing_slashes() {
+        let key = link_key("https://www.Example.com/posts/hello/#comments");
+        assert_eq!(key.as_deref(), Some("example.com/posts/hello"));
+        assert_eq!(link_key("http://example.com/posts/hello"), key);
+
+        assert_ne!(link_key("https://example.com/?p=1"), link_key("https://example.com/?p=2"));
+        assert_ne!(link_key("https://example.com:8080/a"), link_key("https://example.com/a"));
+        assert_eq!(link_key("/relative/link").as_deref(), Some("/relative/link"));
+        assert_eq!(link_key("  "), None);
+    }
+
+    #[test]
+    fn title_keys_ignore_case_punctuation_and_whitespace() {
+        assert_eq!(title_key("Hello,  World!").as_deref(), Some("hello world"));
+        assert_eq!(title_key("hello world"), title_key(" HELLO — World "));
+        assert_ne!(title_key("Hello world"), title_key("Hello world 2"));
+        assert_eq!(title_key("!!!"), None);
+    }
+}
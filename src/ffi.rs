@@ -1,15 +1,48 @@
+use std::path::Path;
 use std::sync::Arc;
 
-use crate::{http::FeedFetcher, sqlite::Store, Core, Error, Feed, FeedEntry};
+use crate::config::Config;
+use crate::rules::{MatchKind, Rule, RuleAction, RuleField};
+use crate::{
+    http::FeedFetcher, sqlite::Store, Core, EntryRevision, Error, Feed, FeedEntry, Folder, ImportReport, PruneReport, RetentionPolicy,
+    SearchFilters, SearchResult, TimelinePage, TimelineQuery,
+};
 
 /// FFICore is the concrete entry point for FFI consumers (e.g. Swift via UniFFI).
 /// It wraps Core with fixed concrete types so the FFI layer sees no generics.
 pub struct FFICore(Core<Store, FeedFetcher>);
 
 impl FFICore {
+    /// Opens the database and applies the settings of ~/.seymour/config.toml,
+    /// as the CLI does. Fails with InvalidArgument when that file can't be
+    /// read or holds an invalid setting; use open to get at the database
+    /// regardless.
     pub fn new() -> Result<Arc<Self>, Error> {
-        let store = Store::new()?;
-        let core = Core::new(store, FeedFetcher {});
+        let config = Config::load(None)?;
+        let store = match &config.db.value {
+            Some(path) => Store::open(path)?,
+            None => Store::new()?,
+        };
+        Self::with_config(store, &config)
+    }
+
+    /// Opens the database at path, e.g. in an app group container shared with
+    /// extensions, with the default settings. No config file is read.
+    pub fn open(path: String) -> Result<Arc<Self>, Error> {
+        let store = Store::open(path)?;
+        Self::with_config(store, &Config::default())
+    }
+
+    /// Opens the database at path with the settings of the config file at
+    /// config_path. Its db setting is ignored.
+    pub fn open_with_config(path: String, config_path: String) -> Result<Arc<Self>, Error> {
+        let config = Config::load(Some(Path::new(&config_path)))?;
+        let store = Store::open(path)?;
+        Self::with_config(store, &config)
+    }
+
+    fn with_config(store: Store, config: &Config) -> Result<Arc<Self>, Error> {
+        let core = Core::new(store, FeedFetcher::from_config(config)?).with_config(config);
         Ok(Arc::new(Self(core)))
     }
 
@@ -21,15 +54,141 @@ impl FFICore {
         self.0.add_feed(url).await
     }
 
+    pub async fn import_opml(&self, xml: String) -> Result<ImportReport, Error> {
+        self.0.import_opml(&xml).await
+    }
+
+    pub fn export_opml(&self) -> Result<String, Error> {
+        self.0.export_opml()
+    }
+
     pub fn get_feed(&self, id: &str) -> Result<Feed, Error> {
         self.0.get_feed(id)
     }
 
+    pub fn remove_feed(&self, id: &str) -> Result<(), Error> {
+        self.0.remove_feed(id)
+    }
+
     pub fn list_entries(&self, feed_id: &str, fetch_all: bool) -> Result<Vec<FeedEntry>, Error> {
         self.0.list_entries(feed_id, fetch_all)
     }
 
-    pub fn list_timeline(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
-        self.0.list_timeline()
+    pub fn list_revisions(&self, entry_id: &str) -> Result<Vec<EntryRevision>, Error> {
+        self.0.list_revisions(entry_id)
+    }
+
+    pub fn list_timeline(&self, query: TimelineQuery) -> Result<TimelinePage, Error> {
+        self.0.list_timeline(&query)
+    }
+
+    pub fn list_pending(&self, feed_id: Option<String>) -> Result<Vec<(String, FeedEntry)>, Error> {
+        self.0.list_pending(feed_id.as_deref())
+    }
+
+    pub fn set_entries_approved(&self, entry_ids: Vec<String>, approved: bool) -> Result<u64, Error> {
+        self.0
+            .set_entries_approved(&entry_ids, approved)
+            .map(|n| n as u64)
+    }
+
+    pub fn set_pending_approved(&self, feed_id: Option<String>, approved: bool) -> Result<u64, Error> {
+        self.0
+            .set_pending_approved(feed_id.as_deref(), approved)
+            .map(|n| n as u64)
+    }
+
+    pub fn set_feed_require_approval(&self, feed_id: &str, require_approval: bool) -> Result<(), Error> {
+        self.0.set_feed_require_approval(feed_id, require_approval)
+    }
+
+    pub fn set_feed_paused(&self, feed_id: &str, paused: bool) -> Result<(), Error> {
+        self.0.set_feed_paused(feed_id, paused)
+    }
+
+    pub fn get_retention(&self) -> Result<RetentionPolicy, Error> {
+        self.0.get_retention()
+    }
+
+    pub fn set_retention(&self, policy: RetentionPolicy) -> Result<(), Error> {
+        self.0.set_retention(&policy)
+    }
+
+    pub fn set_feed_retention(&self, feed_id: &str, policy: Option<RetentionPolicy>) -> Result<(), Error> {
+        self.0.set_feed_retention(feed_id, policy.as_ref())
+    }
+
+    pub fn prune(&self, dry_run: bool, vacuum: bool) -> Result<PruneReport, Error> {
+        self.0.prune(dry_run, vacuum)
+    }
+
+    pub fn set_entry_read(&self, entry_id: &str, read: bool) -> Result<(), Error> {
+        self.0.set_entry_read(entry_id, read)
+    }
+
+    pub fn set_entry_starred(&self, entry_id: &str, starred: bool) -> Result<(), Error> {
+        self.0.set_entry_starred(entry_id, starred)
+    }
+
+    pub fn list_starred(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
+        self.0.list_starred()
+    }
+
+    pub fn search_entries(&self, query: &str, filters: SearchFilters) -> Result<Vec<SearchResult>, Error> {
+        self.0.search_entries(query, &filters)
+    }
+
+    pub fn set_feed_read(&self, feed_id: &str, read: bool) -> Result<u64, Error> {
+        self.0.set_feed_read(feed_id, read).map(|n| n as u64)
+    }
+
+    pub fn mark_all_read(&self) -> Result<u64, Error> {
+        self.0.mark_all_read().map(|n| n as u64)
+    }
+
+    pub fn add_folder(&self, name: &str) -> Result<Folder, Error> {
+        self.0.add_folder(name)
+    }
+
+    pub fn list_folders(&self) -> Result<Vec<Folder>, Error> {
+        self.0.list_folders()
+    }
+
+    pub fn rename_folder(&self, id: &str, name: &str) -> Result<(), Error> {
+        self.0.rename_folder(id, name)
+    }
+
+    pub fn remove_folder(&self, id: &str) -> Result<(), Error> {
+        self.0.remove_folder(id)
+    }
+
+    pub fn add_feed_to_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error> {
+        self.0.add_feed_to_folder(feed_id, folder_id)
+    }
+
+    pub fn remove_feed_from_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error> {
+        self.0.remove_feed_from_folder(feed_id, folder_id)
+    }
+
+    pub fn add_rule(
+        &self,
+        field: RuleField,
+        match_kind: MatchKind,
+        pattern: &str,
+        action: RuleAction,
+    ) -> Result<Rule, Error> {
+        self.0.add_rule(field, match_kind, pattern, &action)
+    }
+
+    pub fn list_rules(&self) -> Result<Vec<Rule>, Error> {
+        self.0.list_rules()
+    }
+
+    pub fn remove_rule(&self, id: &str) -> Result<(), Error> {
+        self.0.remove_rule(id)
+    }
+
+    pub fn apply_rules(&self) -> Result<u64, Error> {
+        self.0.apply_rules().map(|n| n as u64)
     }
 }
@@ -1,10 +1,45 @@
 use chrono::DateTime;
 use serde::Deserialize;
+use xml::common::Position;
 
+use crate::config::Config;
+use crate::CacheValidators;
 use crate::Error;
+use crate::FeedCandidate;
+use crate::FetchResult;
 use crate::Fetcher;
 
-pub struct FeedFetcher {}
+/// FeedFetcher fetches feeds over HTTP(S), reusing one client (and so its
+/// connection pool) across fetches.
+pub struct FeedFetcher {
+    client: reqwest::Client,
+}
+
+impl FeedFetcher {
+    /// Builds a fetcher with the default user agent and timeouts.
+    pub fn new() -> Result<Self, Error> {
+        Self::from_config(&Config::default())
+    }
+
+    /// Builds a fetcher with the user agent and timeouts of a config.
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        // Redirects are followed by fetch itself, to tell permanent ones apart
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(&config.user_agent.value)
+            .connect_timeout(config.connect_timeout.value)
+            .timeout(config.timeout.value)
+            .build()
+            .map_err(|err| Error::Internal(format!("building the HTTP client: {err}")))?;
+        Ok(Self { client })
+    }
+}
+
+/// How many redirects a fetch follows before giving up.
+const MAX_REDIRECTS: usize = 10;
+
+/// The largest response body a fetch reads, in bytes.
+const MAX_BODY_SIZE: u64 = 10 * 1024 * 1024;
 
 #[derive(Debug, Deserialize)]
 struct Rss {
@@ -16,79 +51,802 @@ struct Channel {
     title: String,
     description: String,
     link: String,
-    #[serde(rename = "item")]
+    #[serde(rename = "item", default)]
     items: Vec<Item>,
 }
 
+/// Item is an RSS 2.0 item. Every field is optional in the spec (an item only
+/// needs a title or a description), so nothing is required here and bad items
+/// are weeded out after parsing.
 #[derive(Debug, Deserialize)]
 struct Item {
+    title: Option<String>,
+    link: Option<String>,
+    guid: Option<String>,
+    description: Option<String>,
+    #[serde(rename = "pubDate")]
+    pub_time: Option<String>,
+    author: Option<String>,
+    #[serde(rename = "dc:creator")]
+    creator: Option<String>,
+}
+
+/// Rdf is an RSS 1.0 document, where items are siblings of the channel rather
+/// than children of it.
+#[derive(Debug, Deserialize)]
+struct Rdf {
+    channel: RdfChannel,
+    #[serde(rename = "item", default)]
+    items: Vec<RdfItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RdfChannel {
     title: String,
     link: String,
-    guid: String,
-    description: String,
-    #[serde(rename = "pubDate")]
-    pub_time: String,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RdfItem {
+    #[serde(rename = "@rdf:about")]
+    about: Option<String>,
+    title: String,
+    link: String,
+    description: Option<String>,
+    #[serde(rename = "dc:date")]
+    date: Option<String>,
+    #[serde(rename = "dc:creator")]
+    creator: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtomFeed {
+    title: AtomText,
+    subtitle: Option<AtomText>,
+    id: String,
+    #[serde(rename = "link", default)]
+    links: Vec<AtomLink>,
+    #[serde(rename = "entry", default)]
+    entries: Vec<AtomEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtomEntry {
+    title: AtomText,
+    id: String,
+    #[serde(rename = "link", default)]
+    links: Vec<AtomLink>,
+    updated: Option<String>,
+    published: Option<String>,
+    summary: Option<AtomText>,
+    content: Option<AtomText>,
+    #[serde(rename = "author", default)]
+    authors: Vec<AtomPerson>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtomPerson {
+    name: String,
+}
+
+/// AtomText is a text construct (title, subtitle, summary, content), which may
+/// carry a `type` attribute alongside its text.
+#[derive(Debug, Deserialize)]
+struct AtomText {
+    #[serde(rename = "#text", default)]
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtomLink {
+    #[serde(rename = "@href")]
+    href: String,
+    #[serde(rename = "@rel")]
+    rel: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonFeed {
+    version: String,
+    title: String,
+    home_page_url: Option<String>,
+    feed_url: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    items: Vec<JsonItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonItem {
+    // Version 1 allowed numeric ids, so accept any scalar and stringify it.
+    id: serde_json::Value,
+    url: Option<String>,
+    external_url: Option<String>,
+    title: Option<String>,
+    content_html: Option<String>,
+    content_text: Option<String>,
+    summary: Option<String>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    #[serde(default)]
+    authors: Vec<JsonAuthor>,
+    // Version 1 had a single author
+    author: Option<JsonAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonAuthor {
+    name: Option<String>,
+}
+
+/// Returns the href of the first alternate link. A link without a `rel` is an
+/// alternate link per RFC 4287.
+fn alternate_link(links: &[AtomLink]) -> Option<String> {
+    links
+        .iter()
+        .find(|link| matches!(link.rel.as_deref(), None | Some("alternate")))
+        .map(|link| link.href.clone())
 }
 
 impl Fetcher for FeedFetcher {
-    async fn fetch(&self, url: &str) -> Result<(crate::RemoteFeed, Vec<crate::RemoteEntry>), Error> {
-        let response = reqwest::get(url)
-            .await
-            .map_err(|err| Error::Internal(err.to_string()))?;
+    async fn fetch(&self, url: &str, validators: &CacheValidators) -> Result<FetchResult, Error> {
+        let mut current = reqwest::Url::parse(url)
+            .map_err(|err| Error::InvalidArgument(format!("invalid URL {url}: {err}")))?;
+
+        // The feed has moved for good only as far as the chain of redirects is
+        // unbroken by temporary ones
+        let mut moved_to: Option<String> = None;
+        let mut permanent = true;
+        let mut redirects = 0;
+        let response = loop {
+            let mut request = self.client.get(current.clone());
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+
+            let response = request.send().await.map_err(request_error)?;
+
+            let status = response.status().as_u16();
+            if !matches!(status, 301 | 302 | 303 | 307 | 308) {
+                break response;
+            }
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| Error::Internal(format!("redirect from {current} has no location")))?;
+            let next = current
+                .join(location)
+                .map_err(|err| Error::Internal(format!("bad redirect to {location}: {err}")))?;
+
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Err(Error::Internal(format!("more than {MAX_REDIRECTS} redirects")));
+            }
+            permanent &= matches!(status, 301 | 308);
+            if permanent {
+                moved_to = Some(next.to_string());
+            }
+            current = next;
+        };
+        let moved_to = moved_to.filter(|moved_to| moved_to != url);
 
-        // Handle the codes for better messaging to the user
-        match response.status().into() {
-            400..=499 => {
-                return Err(Error::NotFound);
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(FetchResult::NotModified { moved_to });
+        }
+        if !status.is_success() {
+            return Err(Error::Http {
+                status: status.as_u16(),
+                retry_after: retry_after(response.headers()),
+            });
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let page_url = response.url().clone();
+        let response_validators = CacheValidators {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        };
+
+        let body = read_body(response, content_type.as_deref()).await?;
+
+        if is_html(&body, content_type.as_deref()) {
+            return Ok(FetchResult::Discovered(discover_feeds(&body, &page_url)));
+        }
+
+        let (mut feed, entries) = parse_feed(&body, content_type.as_deref())?;
+        feed.validators = response_validators;
+        feed.moved_to = moved_to;
+        Ok(FetchResult::Fetched(feed, entries))
+    }
+}
+
+/// Parses a feed document. JSON Feeds are recognized by their content type or
+/// by sniffing the body; XML formats are picked from the root element.
+fn parse_feed(
+    body: &str,
+    content_type: Option<&str>,
+) -> Result<(crate::RemoteFeed, Vec<crate::RemoteEntry>), Error> {
+    let is_json = content_type.is_some_and(|ct| {
+        let mime = ct.split(';').next().unwrap_or_default().trim();
+        mime.eq_ignore_ascii_case("application/feed+json")
+            || mime.eq_ignore_ascii_case("application/json")
+    });
+    if is_json || body.trim_start_matches('\u{feff}').trim_start().starts_with('{') {
+        return parse_json_feed(body);
+    }
+
+    match root_element(body) {
+        Some("rss") => parse_rss(body),
+        Some("feed") => parse_atom(body),
+        Some("rdf:RDF") => parse_rdf(body),
+        Some(other) => Err(unparsed(format!("unsupported feed format: <{other}>"))),
+        None => Err(unparsed("response is not a feed document".to_string())),
+    }
+}
+
+/// A parse error without a location.
+fn unparsed(message: String) -> Error {
+    Error::Parse {
+        message,
+        line: None,
+        column: None,
+    }
+}
+
+fn xml_error(err: serde_xml_rs::Error) -> Error {
+    match err {
+        serde_xml_rs::Error::Reader(err) => {
+            let position = err.position();
+            // The message starts with the position, which is kept separately
+            let message = err.to_string();
+            let message = message.split_once(' ').map_or(message.as_str(), |(_, m)| m);
+            Error::Parse {
+                message: message.to_string(),
+                line: Some(position.row + 1),
+                column: Some(position.column + 1),
             }
-            500..=599 => {
-                return Err(Error::Internal(
-                    "error received from the remote server".to_string(),
-                ));
+        }
+        err => unparsed(err.to_string()),
+    }
+}
+
+fn json_error(err: serde_json::Error) -> Error {
+    if err.line() == 0 {
+        return unparsed(err.to_string());
+    }
+    // The message ends with the position, which is kept separately
+    let message = err.to_string();
+    let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(m, _)| m);
+    Error::Parse {
+        message: message.to_string(),
+        line: Some(err.line() as u64),
+        column: Some(err.column() as u64),
+    }
+}
+
+/// Classifies a failed request. reqwest doesn't tell TLS failures apart, so
+/// they are picked out by the errors that caused them.
+fn request_error(err: reqwest::Error) -> Error {
+    if err.is_timeout() {
+        return Error::Timeout;
+    }
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        let text = cause.to_string();
+        let lower = text.to_ascii_lowercase();
+        if ["certificate", "tls", "ssl", "handshake"].iter().any(|word| lower.contains(word)) {
+            return Error::Tls(text);
+        }
+        message = format!("{message}: {text}");
+        source = cause.source();
+    }
+    Error::Network(message)
+}
+
+/// Reads the Retry-After header, given either as a number of seconds or as an
+/// HTTP date. Dates in the past mean no wait.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let seconds = date.timestamp() - chrono::Utc::now().timestamp();
+    Some(u64::try_from(seconds).unwrap_or_default())
+}
+
+/// Reads the response body, up to MAX_BODY_SIZE, decoding it with the charset
+/// from the content type, or as UTF-8.
+async fn read_body(mut response: reqwest::Response, content_type: Option<&str>) -> Result<String, Error> {
+    let too_large = Error::TooLarge { limit: MAX_BODY_SIZE };
+    if response.content_length().is_some_and(|len| len > MAX_BODY_SIZE) {
+        return Err(too_large);
+    }
+    let mut body = vec![];
+    while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+        if (body.len() + chunk.len()) as u64 > MAX_BODY_SIZE {
+            return Err(too_large);
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let encoding = content_type
+        .into_iter()
+        .flat_map(|ct| ct.split(';').skip(1))
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, label)| encoding_rs::Encoding::for_label(label.trim().trim_matches('"').as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(&body);
+    Ok(text.into_owned())
+}
+
+/// Tells web pages apart from feeds, which are sometimes served as text/html.
+/// HTML5 pages may leave out the `<html>` tag and start with their head.
+fn is_html(body: &str, content_type: Option<&str>) -> bool {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .unwrap_or_default()
+        .trim();
+    if mime.eq_ignore_ascii_case("application/xhtml+xml") {
+        return true;
+    }
+    if body.trim_start_matches('\u{feff}').trim_start().starts_with('{') {
+        return false;
+    }
+    const PAGE_ROOTS: &[&str] = &["html", "head", "body", "meta", "link"];
+    match root_element(body) {
+        Some("rss" | "feed" | "rdf:RDF") => false,
+        Some(root) if PAGE_ROOTS.iter().any(|tag| root.eq_ignore_ascii_case(tag)) => true,
+        _ => mime.eq_ignore_ascii_case("text/html"),
+    }
+}
+
+/// The feed types pages advertise in their alternate links.
+const FEED_TYPES: &[&str] = &[
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+    "application/json",
+];
+
+/// Finds the feeds a web page advertises in its head with
+/// `<link rel="alternate" type="..." href="...">`, resolving their URLs against
+/// the page (or its `<base>`). Candidates are in document order, without
+/// duplicates.
+fn discover_feeds(body: &str, page_url: &reqwest::Url) -> Vec<FeedCandidate> {
+    let mut base = page_url.clone();
+    let mut candidates: Vec<FeedCandidate> = vec![];
+    for (name, attrs) in html_tags(body) {
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.trim())
+        };
+        // Feeds are advertised in the head
+        if name.eq_ignore_ascii_case("body") {
+            break;
+        }
+        if name.eq_ignore_ascii_case("base") {
+            if let Some(url) = attr("href").and_then(|href| page_url.join(href).ok()) {
+                base = url;
             }
-            200..=299 => {} // Continue to parse and output
-            _ => {}         // Continue to parse and output
+            continue;
+        }
+        if !name.eq_ignore_ascii_case("link") {
+            continue;
         }
 
-        let body = response
-            .text()
-            .await
-            .map_err(|err| Error::Internal(err.to_string()))?;
+        let is_alternate = attr("rel").is_some_and(|rel| {
+            rel.split_ascii_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("alternate"))
+        });
+        let content_type = attr("type")
+            .map(|t| t.to_ascii_lowercase())
+            .filter(|t| FEED_TYPES.contains(&t.as_str()));
+        let url = attr("href").and_then(|href| base.join(href).ok());
+        let (true, Some(content_type), Some(url)) = (is_alternate, content_type, url) else {
+            continue;
+        };
+        if candidates.iter().any(|c| c.url == url.as_str()) {
+            continue;
+        }
+        candidates.push(FeedCandidate {
+            url: url.to_string(),
+            title: attr("title").filter(|t| !t.is_empty()).map(str::to_string),
+            content_type,
+        });
+    }
+    candidates
+}
+
+/// Yields the name and attributes of each start tag in an HTML document,
+/// skipping comments and the contents of scripts and styles. Attribute values
+/// have the common character references decoded.
+fn html_tags(body: &str) -> impl Iterator<Item = (&str, Vec<(&str, String)>)> {
+    let mut rest = body;
+    std::iter::from_fn(move || loop {
+        rest = &rest[rest.find('<')?..];
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        let after = &rest[1..];
+        let name_len = after
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(after.len());
+        if name_len == 0 {
+            rest = after;
+            continue;
+        }
+        let name = &after[..name_len];
+        let (attrs, remainder) = html_attributes(&after[name_len..]);
+        rest = remainder;
 
-        let rss: Rss =
-            serde_xml_rs::from_str(&body).map_err(|err| Error::Internal(err.to_string()))?;
+        // Script and style contents are text that may well contain "<link"
+        if name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style") {
+            let close = format!("</{name}");
+            rest = rest
+                .as_bytes()
+                .windows(close.len())
+                .position(|w| w.eq_ignore_ascii_case(close.as_bytes()))
+                .map_or("", |end| &rest[end..]);
+        }
+        return Some((name, attrs));
+    })
+}
+
+/// Parses attributes up to the end of a tag, returning them along with the rest
+/// of the document after the tag.
+fn html_attributes(mut rest: &str) -> (Vec<(&str, String)>, &str) {
+    let mut attrs = vec![];
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return (attrs, rest);
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return (attrs, after);
+        }
+
+        let key_len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len())
+            .max(1);
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
 
-        // Parse the top level
-        let feed = crate::RemoteFeed {
-            url: rss.channel.link,
-            title: rss.channel.title,
-            description: rss.channel.description,
+        let Some(after) = rest.strip_prefix('=') else {
+            attrs.push((key, String::new()));
+            continue;
         };
+        rest = after.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = rest[1..].find(quote).map_or(rest.len(), |i| i + 1);
+                let value = &rest[1..end];
+                rest = rest.get(end + 1..).unwrap_or("");
+                value
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(rest.len());
+                let value = &rest[..end];
+                rest = &rest[end..];
+                value
+            }
+        };
+        attrs.push((key, decode_entities(value)));
+    }
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Returns the name of the document's root element, skipping over the XML
+/// declaration, processing instructions, comments and the doctype.
+fn root_element(body: &str) -> Option<&str> {
+    let mut rest = body.trim_start_matches('\u{feff}');
+    loop {
+        rest = &rest[rest.find('<')?..];
+        if let Some(after) = rest.strip_prefix("<?") {
+            rest = &after[after.find("?>")? + 2..];
+        } else if let Some(after) = rest.strip_prefix("<!--") {
+            rest = &after[after.find("-->")? + 3..];
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            rest = &after[after.find('>')? + 1..];
+        } else {
+            let name = &rest[1..];
+            let end = name
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .unwrap_or(name.len());
+            return Some(&name[..end]);
+        }
+    }
+}
+
+fn parse_rss(body: &str) -> Result<(crate::RemoteFeed, Vec<crate::RemoteEntry>), Error> {
+    let rss: Rss =
+        serde_xml_rs::from_str(body).map_err(xml_error)?;
+
+    // Parse the entries
+    let mut entries = vec![];
+    let mut skipped_entries = vec![];
+    for (index, item) in rss.channel.items.into_iter().enumerate() {
+        let title = non_empty(item.title);
+        let description = non_empty(item.description);
+        if title.is_none() && description.is_none() {
+            skipped_entries.push(format!(
+                "item {}: missing both title and description",
+                index + 1
+            ));
+            continue;
+        }
 
-        // Parse the entries
-        let mut entries = vec![];
-        for item in rss.channel.items {
-            let publish_time_unix_secs = DateTime::parse_from_rfc2822(&item.pub_time)
-                .ok()
-                .and_then(|dt| u64::try_from(dt.timestamp()).ok());
-
-            entries.push(crate::RemoteEntry {
-                title: item.title,
-                description: item.description,
-                guid: item.guid,
-                link: item.link,
-                publish_time_unix_secs,
+        let link = non_empty(item.link);
+        let guid = non_empty(item.guid)
+            .or_else(|| link.clone())
+            .unwrap_or_else(|| {
+                content_hash(&[
+                    title.as_deref().unwrap_or_default(),
+                    description.as_deref().unwrap_or_default(),
+                ])
             });
+
+        let publish_time_unix_secs = item
+            .pub_time
+            .and_then(|ts| DateTime::parse_from_rfc2822(ts.trim()).ok())
+            .and_then(|dt| u64::try_from(dt.timestamp()).ok());
+
+        entries.push(crate::RemoteEntry {
+            title: title.unwrap_or_default(),
+            description: description.unwrap_or_default(),
+            guid,
+            link: link.unwrap_or_default(),
+            publish_time_unix_secs,
+            author: non_empty(item.creator).or_else(|| non_empty(item.author)),
+        });
+    }
+
+    // Parse the top level
+    let feed = crate::RemoteFeed {
+        url: rss.channel.link,
+        title: rss.channel.title,
+        description: rss.channel.description,
+        skipped_entries,
+        validators: CacheValidators::default(),
+        moved_to: None,
+    };
+
+    Ok((feed, entries))
+}
+
+/// Joins the names of an entry's authors, or None if it has none.
+fn join_authors(names: impl Iterator<Item = String>) -> Option<String> {
+    let names: Vec<String> = names
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+/// Treats missing and blank values the same.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// Derives a stable identifier from an item's content, for items that have
+/// neither a guid nor a link. Uses 64-bit FNV-1a, which unlike std's hasher is
+/// guaranteed not to change between builds.
+fn content_hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            // Separate the parts so ("ab", "c") and ("a", "bc") differ
+            hash ^= 0xff;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        for byte in part.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
         }
+    }
+    format!("fnv1a:{hash:016x}")
+}
+
+fn parse_rdf(body: &str) -> Result<(crate::RemoteFeed, Vec<crate::RemoteEntry>), Error> {
+    // Channel, image, items and textinput may appear in any order under the
+    // root element.
+    let rdf: Rdf = serde_xml_rs::SerdeXml::new()
+        .overlapping_sequences(true)
+        .from_str(body)
+        .map_err(xml_error)?;
+
+    let feed = crate::RemoteFeed {
+        url: rdf.channel.link,
+        title: rdf.channel.title,
+        description: rdf.channel.description.unwrap_or_default(),
+        skipped_entries: vec![],
+        validators: CacheValidators::default(),
+        moved_to: None,
+    };
+
+    let mut entries = vec![];
+    for item in rdf.items {
+        let publish_time_unix_secs = item
+            .date
+            .as_deref()
+            .and_then(parse_w3c_date)
+            .and_then(|ts| u64::try_from(ts).ok());
+
+        entries.push(crate::RemoteEntry {
+            title: item.title,
+            description: item.description.unwrap_or_default(),
+            guid: item.about.unwrap_or_else(|| item.link.clone()),
+            link: item.link,
+            publish_time_unix_secs,
+            author: non_empty(item.creator),
+        });
+    }
+
+    Ok((feed, entries))
+}
+
+/// Parses a W3C-DTF date as used by Dublin Core's `dc:date`, which is either a
+/// full RFC 3339 timestamp or a bare date (taken as midnight UTC).
+fn parse_w3c_date(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.timestamp());
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+fn parse_atom(body: &str) -> Result<(crate::RemoteFeed, Vec<crate::RemoteEntry>), Error> {
+    // Atom allows links to be interleaved with the other children of a feed or
+    // entry, so repeated elements can't be assumed to be consecutive.
+    let atom: AtomFeed = serde_xml_rs::SerdeXml::new()
+        .overlapping_sequences(true)
+        .from_str(body)
+        .map_err(xml_error)?;
 
-        Ok((feed, entries))
+    let feed = crate::RemoteFeed {
+        url: alternate_link(&atom.links).unwrap_or(atom.id),
+        title: atom.title.value,
+        description: atom.subtitle.map(|s| s.value).unwrap_or_default(),
+        skipped_entries: vec![],
+        validators: CacheValidators::default(),
+        moved_to: None,
+    };
+
+    let mut entries = vec![];
+    for entry in atom.entries {
+        // Prefer the original publication time, falling back to the last
+        // modification when the feed doesn't provide one.
+        let publish_time_unix_secs = entry
+            .published
+            .or(entry.updated)
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts.trim()).ok())
+            .and_then(|dt| u64::try_from(dt.timestamp()).ok());
+
+        entries.push(crate::RemoteEntry {
+            title: entry.title.value,
+            description: entry
+                .content
+                .or(entry.summary)
+                .map(|text| text.value)
+                .unwrap_or_default(),
+            link: alternate_link(&entry.links).unwrap_or_else(|| entry.id.clone()),
+            guid: entry.id,
+            publish_time_unix_secs,
+            author: join_authors(entry.authors.into_iter().map(|a| a.name)),
+        });
     }
+
+    Ok((feed, entries))
+}
+
+fn parse_json_feed(body: &str) -> Result<(crate::RemoteFeed, Vec<crate::RemoteEntry>), Error> {
+    let json: JsonFeed =
+        serde_json::from_str(body).map_err(json_error)?;
+
+    if !json.version.starts_with("https://jsonfeed.org/version/1") {
+        return Err(unparsed(format!(
+            "unsupported JSON Feed version: {}",
+            json.version
+        )));
+    }
+
+    let feed = crate::RemoteFeed {
+        url: json.home_page_url.or(json.feed_url).unwrap_or_default(),
+        title: json.title,
+        description: json.description.unwrap_or_default(),
+        skipped_entries: vec![],
+        validators: CacheValidators::default(),
+        moved_to: None,
+    };
+
+    let mut entries = vec![];
+    for item in json.items {
+        let guid = match item.id {
+            serde_json::Value::String(id) => id,
+            other => other.to_string(),
+        };
+
+        let publish_time_unix_secs = item
+            .date_published
+            .or(item.date_modified)
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts.trim()).ok())
+            .and_then(|dt| u64::try_from(dt.timestamp()).ok());
+
+        entries.push(crate::RemoteEntry {
+            title: item.title.unwrap_or_default(),
+            description: item
+                .content_html
+                .or(item.content_text)
+                .or(item.summary)
+                .unwrap_or_default(),
+            link: item
+                .url
+                .or(item.external_url)
+                .unwrap_or_else(|| guid.clone()),
+            guid,
+            publish_time_unix_secs,
+            author: join_authors(
+                item.authors
+                    .into_iter()
+                    .chain(item.author)
+                    .filter_map(|a| a.name),
+            ),
+        });
+    }
+
+    Ok((feed, entries))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    async fn fetch(url: &str) -> (crate::RemoteFeed, Vec<crate::RemoteEntry>) {
+        match FeedFetcher::new()
+            .unwrap()
+            .fetch(url, &CacheValidators::default())
+            .await
+            .unwrap()
+        {
+            FetchResult::Fetched(feed, entries) => (feed, entries),
+            FetchResult::NotModified { .. } => panic!("expected a feed, got 304"),
+            FetchResult::Discovered(_) => panic!("expected a feed, got a web page"),
+        }
+    }
+
     const SAMPLE_RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
 <rss version="2.0">
 <channel>
@@ -103,6 +861,7 @@ mod tests {
     <pubDate>Thu, 20 Nov 2025 14:19:14 +0000</pubDate>
     <link>https://apenwarr.ca/log/20251120</link>
     <guid isPermaLink="true">https://apenwarr.ca/log/20251120</guid>
+    <author>apenwarr@example.com (Avery Pennarun)</author>
     <description>&lt;p&gt;LLMs interconnect things. Anything. To anything.&lt;/p&gt;</description>
   </item>
   <item>
@@ -115,6 +874,33 @@ mod tests {
 </channel>
 </rss>"#;
 
+    const SAMPLE_ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Julia Evans</title>
+  <subtitle>A blog about computers</subtitle>
+  <link href="https://jvns.ca/atom.xml" rel="self"/>
+  <link href="https://jvns.ca/" rel="alternate"/>
+  <updated>2025-10-10T00:00:00+00:00</updated>
+  <id>https://jvns.ca/</id>
+  <entry>
+    <title>Notes on switching to Helix from vim</title>
+    <link href="https://jvns.ca/blog/2025/10/10/notes-on-switching-to-helix-from-vim/" rel="alternate"/>
+    <id>https://jvns.ca/blog/2025/10/10/notes-on-switching-to-helix-from-vim/</id>
+    <published>2025-10-10T09:30:00-04:00</published>
+    <updated>2025-10-11T00:00:00+00:00</updated>
+    <summary>A short summary.</summary>
+    <author><name>Julia Evans</name></author>
+    <content type="html">&lt;p&gt;Hello! Earlier this summer I switched to Helix.&lt;/p&gt;</content>
+  </entry>
+  <entry>
+    <title>New zine: The Secret Rules of the Terminal</title>
+    <id>tag:jvns.ca,2025:the-secret-rules-of-the-terminal</id>
+    <updated>2025-06-24T12:00:00Z</updated>
+    <link href="https://jvns.ca/blog/2025/06/24/new-zine--the-secret-rules-of-the-terminal/"/>
+    <summary type="text">I just released a zine.</summary>
+  </entry>
+</feed>"#;
+
     #[tokio::test]
     async fn parses_rss() {
         let mut server = mockito::Server::new_async().await;
@@ -126,7 +912,7 @@ mod tests {
             .create_async()
             .await;
 
-        let (feed, entries) = FeedFetcher{}.fetch(&server.url()).await.unwrap();
+        let (feed, entries) = fetch(&server.url()).await;
 
         assert_eq!(feed.title, "apenwarr");
         assert_eq!(feed.description, "apenwarr - NITLog");
@@ -141,6 +927,10 @@ mod tests {
         assert_eq!(entries[0].link, "https://apenwarr.ca/log/20251120");
         assert_eq!(entries[0].guid, "https://apenwarr.ca/log/20251120");
         assert_eq!(entries[0].publish_time_unix_secs, Some(1763648354));
+        assert_eq!(
+            entries[0].author.as_deref(),
+            Some("apenwarr@example.com (Avery Pennarun)")
+        );
 
         assert_eq!(entries[1].title, "Billionaire math");
         assert_eq!(entries[1].link, "https://apenwarr.ca/log/20250711");
@@ -148,8 +938,392 @@ mod tests {
         assert_eq!(entries[1].publish_time_unix_secs, Some(1752235200));
     }
 
+    const SAMPLE_RSS_MISSING_FIELDS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+<channel>
+  <title>Sparse</title>
+  <description>Items with missing fields</description>
+  <link>https://example.com/</link>
+  <item>
+    <title>No guid, has link</title>
+    <link>https://example.com/posts/1</link>
+  </item>
+  <item>
+    <description>Only a description</description>
+    <pubDate>not a date</pubDate>
+  </item>
+  <item>
+    <link>https://example.com/posts/3</link>
+    <guid></guid>
+  </item>
+  <item>
+    <title>Complete</title>
+    <link>https://example.com/posts/4</link>
+    <guid isPermaLink="false">post-4</guid>
+    <description>All fields present</description>
+    <pubDate>Fri, 11 Jul 2025 12:00:00 +0000</pubDate>
+  </item>
+</channel>
+</rss>"#;
+
+    #[tokio::test]
+    async fn parses_rss_with_missing_item_fields() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "application/rss+xml")
+            .with_body(SAMPLE_RSS_MISSING_FIELDS)
+            .create_async()
+            .await;
+
+        let (feed, entries) = fetch(&server.url()).await;
+
+        // The item with neither a title nor a description is dropped and reported
+        assert_eq!(
+            feed.skipped_entries,
+            vec!["item 3: missing both title and description".to_string()]
+        );
+        assert_eq!(entries.len(), 3);
+
+        // Guid falls back to the link
+        assert_eq!(entries[0].title, "No guid, has link");
+        assert_eq!(entries[0].guid, "https://example.com/posts/1");
+        assert_eq!(entries[0].description, "");
+        assert_eq!(entries[0].publish_time_unix_secs, None);
+
+        // Then to a hash of the content, which is stable across fetches
+        assert_eq!(entries[1].title, "");
+        assert_eq!(entries[1].link, "");
+        assert_eq!(entries[1].guid, content_hash(&["", "Only a description"]));
+        assert!(entries[1].guid.starts_with("fnv1a:"));
+        assert_eq!(entries[1].publish_time_unix_secs, None);
+
+        assert_eq!(entries[2].guid, "post-4");
+        assert_eq!(entries[2].publish_time_unix_secs, Some(1752235200));
+    }
+
+    #[test]
+    fn content_hash_separates_parts() {
+        assert_ne!(content_hash(&["ab", "c"]), content_hash(&["a", "bc"]));
+        assert_eq!(content_hash(&["a", "b"]), content_hash(&["a", "b"]));
+    }
+
     #[tokio::test]
-    async fn returns_not_found_on_4xx() {
+    async fn parses_atom() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "application/atom+xml")
+            .with_body(SAMPLE_ATOM)
+            .create_async()
+            .await;
+
+        let (feed, entries) = fetch(&server.url()).await;
+
+        assert_eq!(feed.title, "Julia Evans");
+        assert_eq!(feed.description, "A blog about computers");
+        assert_eq!(feed.url, "https://jvns.ca/");
+
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].title, "Notes on switching to Helix from vim");
+        assert_eq!(
+            entries[0].link,
+            "https://jvns.ca/blog/2025/10/10/notes-on-switching-to-helix-from-vim/"
+        );
+        assert_eq!(
+            entries[0].guid,
+            "https://jvns.ca/blog/2025/10/10/notes-on-switching-to-helix-from-vim/"
+        );
+        assert_eq!(
+            entries[0].description,
+            "<p>Hello! Earlier this summer I switched to Helix.</p>"
+        );
+        // Uses <published> over <updated>
+        assert_eq!(entries[0].publish_time_unix_secs, Some(1760103000));
+        assert_eq!(entries[0].author.as_deref(), Some("Julia Evans"));
+
+        assert_eq!(entries[1].title, "New zine: The Secret Rules of the Terminal");
+        assert_eq!(
+            entries[1].link,
+            "https://jvns.ca/blog/2025/06/24/new-zine--the-secret-rules-of-the-terminal/"
+        );
+        assert_eq!(entries[1].guid, "tag:jvns.ca,2025:the-secret-rules-of-the-terminal");
+        assert_eq!(entries[1].description, "I just released a zine.");
+        assert_eq!(entries[1].publish_time_unix_secs, Some(1750766400));
+    }
+
+    const SAMPLE_JSON_FEED: &str = r#"{
+  "version": "https://jsonfeed.org/version/1.1",
+  "title": "Manton Reece",
+  "home_page_url": "https://www.manton.org/",
+  "feed_url": "https://www.manton.org/feed.json",
+  "description": "Micro.blog founder",
+  "items": [
+    {
+      "id": "http://manton2.micro.blog/2025/11/03/152314.html",
+      "url": "https://www.manton.org/2025/11/03/152314.html",
+      "title": "Indie web progress",
+      "content_html": "<p>Working on Micro.blog today.</p>",
+      "date_published": "2025-11-03T10:23:14-05:00",
+      "authors": [{ "name": "Manton Reece" }]
+    },
+    {
+      "id": 42,
+      "content_text": "Short post without a title or url.",
+      "date_modified": "2025-11-01T08:00:00Z"
+    }
+  ]
+}"#;
+
+    #[tokio::test]
+    async fn parses_json_feed() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "application/feed+json")
+            .with_body(SAMPLE_JSON_FEED)
+            .create_async()
+            .await;
+
+        let (feed, entries) = fetch(&server.url()).await;
+
+        assert_eq!(feed.title, "Manton Reece");
+        assert_eq!(feed.description, "Micro.blog founder");
+        assert_eq!(feed.url, "https://www.manton.org/");
+
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].title, "Indie web progress");
+        assert_eq!(entries[0].link, "https://www.manton.org/2025/11/03/152314.html");
+        assert_eq!(entries[0].guid, "http://manton2.micro.blog/2025/11/03/152314.html");
+        assert_eq!(entries[0].description, "<p>Working on Micro.blog today.</p>");
+        assert_eq!(entries[0].publish_time_unix_secs, Some(1762183394));
+        assert_eq!(entries[0].author.as_deref(), Some("Manton Reece"));
+
+        assert_eq!(entries[1].title, "");
+        assert_eq!(entries[1].guid, "42");
+        assert_eq!(entries[1].link, "42");
+        assert_eq!(entries[1].description, "Short post without a title or url.");
+        assert_eq!(entries[1].publish_time_unix_secs, Some(1761984000));
+    }
+
+    #[tokio::test]
+    async fn sniffs_json_feed_without_content_type() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body(SAMPLE_JSON_FEED)
+            .create_async()
+            .await;
+
+        let (feed, entries) = fetch(&server.url()).await;
+
+        assert_eq!(feed.title, "Manton Reece");
+        assert_eq!(entries.len(), 2);
+    }
+
+    const SAMPLE_RDF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rdf:RDF
+  xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+  xmlns:dc="http://purl.org/dc/elements/1.1/"
+  xmlns="http://purl.org/rss/1.0/">
+  <channel rdf:about="https://export.arxiv.org/rss/cs.DB">
+    <title>cs.DB updates on arXiv.org</title>
+    <link>https://arxiv.org/</link>
+    <description>Computer Science -- Databases</description>
+    <items>
+      <rdf:Seq>
+        <rdf:li rdf:resource="https://arxiv.org/abs/2511.00001"/>
+        <rdf:li rdf:resource="https://arxiv.org/abs/2511.00002"/>
+      </rdf:Seq>
+    </items>
+  </channel>
+  <item rdf:about="https://arxiv.org/abs/2511.00001">
+    <title>Learned Indexes Revisited</title>
+    <link>https://arxiv.org/abs/2511.00001</link>
+    <description>We revisit learned index structures.</description>
+    <dc:date>2025-11-03T09:00:00Z</dc:date>
+    <dc:creator>Ada Lovelace</dc:creator>
+  </item>
+  <item rdf:about="https://arxiv.org/abs/2511.00002">
+    <title>Query Optimization at Scale</title>
+    <link>https://arxiv.org/abs/2511.00002</link>
+    <dc:date>2025-11-02</dc:date>
+  </item>
+</rdf:RDF>"#;
+
+    #[tokio::test]
+    async fn parses_rdf() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "application/rdf+xml")
+            .with_body(SAMPLE_RDF)
+            .create_async()
+            .await;
+
+        let (feed, entries) = fetch(&server.url()).await;
+
+        assert_eq!(feed.title, "cs.DB updates on arXiv.org");
+        assert_eq!(feed.description, "Computer Science -- Databases");
+        assert_eq!(feed.url, "https://arxiv.org/");
+
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].title, "Learned Indexes Revisited");
+        assert_eq!(entries[0].link, "https://arxiv.org/abs/2511.00001");
+        assert_eq!(entries[0].guid, "https://arxiv.org/abs/2511.00001");
+        assert_eq!(entries[0].description, "We revisit learned index structures.");
+        assert_eq!(entries[0].publish_time_unix_secs, Some(1762160400));
+        assert_eq!(entries[0].author.as_deref(), Some("Ada Lovelace"));
+
+        assert_eq!(entries[1].title, "Query Optimization at Scale");
+        assert_eq!(entries[1].description, "");
+        // Bare dates are taken as midnight UTC
+        assert_eq!(entries[1].publish_time_unix_secs, Some(1762041600));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_document() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body("<?xml version=\"1.0\"?><opml version=\"2.0\"></opml>")
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
+
+        assert!(matches!(result, Err(Error::Parse { line: None, .. })));
+    }
+
+    #[tokio::test]
+    async fn reports_where_parsing_failed() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body("<?xml version=\"1.0\"?>\n<rss>\n  <channel><title>Oops</channel>\n</rss>")
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
+
+        let Err(Error::Parse { line, column, .. }) = result else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(line, Some(3));
+        assert!(column.is_some());
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_the_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body(vec![b' '; MAX_BODY_SIZE as usize + 1])
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
+
+        assert!(matches!(result, Err(Error::TooLarge { .. })));
+    }
+
+    #[tokio::test]
+    async fn returns_network_error_when_unreachable() {
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch("http://127.0.0.1:1/", &CacheValidators::default())
+            .await;
+
+        assert!(matches!(result, Err(Error::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn returns_cache_validators() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "application/rss+xml")
+            .with_header("etag", "\"abc123\"")
+            .with_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_body(SAMPLE_RSS)
+            .create_async()
+            .await;
+
+        let (feed, _) = fetch(&server.url()).await;
+
+        assert_eq!(feed.validators.etag.as_deref(), Some("\"abc123\""));
+        assert_eq!(
+            feed.validators.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_conditional_headers_and_handles_not_modified() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/")
+            .match_header("if-none-match", "\"abc123\"")
+            .match_header("if-modified-since", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_status(304)
+            .create_async()
+            .await;
+
+        let validators = CacheValidators {
+            etag: Some("\"abc123\"".into()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".into()),
+        };
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &validators)
+            .await
+            .unwrap();
+
+        assert!(matches!(result, FetchResult::NotModified { moved_to: None }));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn omits_conditional_headers_without_validators() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .match_header("if-modified-since", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(SAMPLE_RSS)
+            .create_async()
+            .await;
+
+        fetch(&server.url()).await;
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn returns_http_status_on_4xx() {
         let mut server = mockito::Server::new_async().await;
         let _mock = server
             .mock("GET", "/")
@@ -157,13 +1331,19 @@ mod tests {
             .create_async()
             .await;
 
-        let result = FeedFetcher{}.fetch(&server.url()).await;
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
 
-        assert!(matches!(result, Err(Error::NotFound)));
+        assert!(matches!(
+            result,
+            Err(Error::Http { status: 404, retry_after: None })
+        ));
     }
 
     #[tokio::test]
-    async fn returns_internal_error_on_5xx() {
+    async fn returns_http_status_on_5xx() {
         let mut server = mockito::Server::new_async().await;
         let _mock = server
             .mock("GET", "/")
@@ -171,7 +1351,249 @@ mod tests {
             .create_async()
             .await;
 
-        let result = FeedFetcher{}.fetch(&server.url()).await;
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::Http { status: 500, retry_after: None })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_gone_on_410() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(410)
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::Http { status: 410, retry_after: None })
+        ));
+    }
+
+    #[tokio::test]
+    async fn returns_retry_after_on_503() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(503)
+            .with_header("retry-after", "120")
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::Http { status: 503, retry_after: Some(120) })
+        ));
+    }
+
+    const SAMPLE_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>A Blog</title>
+  <!-- <link rel="alternate" type="application/rss+xml" href="/commented.xml"> -->
+  <script>document.write('<link rel="alternate" type="application/rss+xml" href="/scripted.xml">')</script>
+  <link rel="stylesheet" href="/style.css">
+  <link rel="alternate" type="application/rss+xml" title="Posts &amp; Notes" href="/feed.xml">
+  <LINK REL="Alternate" TYPE="application/atom+xml" HREF='https://cdn.example.com/atom.xml'>
+  <link rel=alternate type=application/rss+xml href=/feed.xml>
+  <link rel="alternate" hreflang="fr" href="/fr/">
+</head>
+<body>
+  <link rel="alternate" type="application/rss+xml" href="/body.xml">
+</body>
+</html>"#;
+
+    #[test]
+    fn discovers_feeds_in_page() {
+        let page_url = reqwest::Url::parse("https://blog.example.com/posts/").unwrap();
+        let candidates = discover_feeds(SAMPLE_PAGE, &page_url);
+
+        assert_eq!(
+            candidates,
+            vec![
+                FeedCandidate {
+                    url: "https://blog.example.com/feed.xml".into(),
+                    title: Some("Posts & Notes".into()),
+                    content_type: "application/rss+xml".into(),
+                },
+                FeedCandidate {
+                    url: "https://cdn.example.com/atom.xml".into(),
+                    title: None,
+                    content_type: "application/atom+xml".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_feeds_against_base() {
+        let page_url = reqwest::Url::parse("https://blog.example.com/posts/1").unwrap();
+        let page = r#"<html><head><base href="/blog/">
+            <link rel="alternate" type="application/feed+json" href="feed.json"></head></html>"#;
+
+        let candidates = discover_feeds(page, &page_url);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].url, "https://blog.example.com/blog/feed.json");
+    }
+
+    #[tokio::test]
+    async fn returns_discovered_feeds_for_html() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_body(SAMPLE_PAGE)
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await
+            .unwrap();
+
+        let FetchResult::Discovered(candidates) = result else {
+            panic!("expected discovered feeds");
+        };
+        assert_eq!(candidates[0].url, format!("{}/feed.xml", server.url()));
+    }
+
+    #[tokio::test]
+    async fn returns_discovered_feeds_for_html_without_html_tag() {
+        let mut server = mockito::Server::new_async().await;
+        let page = r#"<!doctype html>
+<meta charset="utf-8">
+<title>Some Blog</title>
+<link rel="alternate" type="application/atom+xml" href="/atom.xml">
+<p>Hello</p>"#;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(page)
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&server.url(), &CacheValidators::default())
+            .await
+            .unwrap();
+
+        let FetchResult::Discovered(candidates) = result else {
+            panic!("expected discovered feeds");
+        };
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].url, format!("{}/atom.xml", server.url()));
+    }
+
+    #[tokio::test]
+    async fn parses_feed_served_as_html() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(SAMPLE_RSS)
+            .create_async()
+            .await;
+
+        let (_, entries) = fetch(&server.url()).await;
+
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn follows_permanent_redirects() {
+        let mut server = mockito::Server::new_async().await;
+        let _old = server
+            .mock("GET", "/old")
+            .with_status(301)
+            .with_header("location", "/newer")
+            .create_async()
+            .await;
+        let _newer = server
+            .mock("GET", "/newer")
+            .with_status(308)
+            .with_header("location", "/newest")
+            .create_async()
+            .await;
+        let _newest = server
+            .mock("GET", "/newest")
+            .with_status(200)
+            .with_body(SAMPLE_RSS)
+            .create_async()
+            .await;
+
+        let (feed, entries) = fetch(&format!("{}/old", server.url())).await;
+
+        assert_eq!(feed.moved_to, Some(format!("{}/newest", server.url())));
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn temporary_redirects_dont_move_feed() {
+        let mut server = mockito::Server::new_async().await;
+        let _old = server
+            .mock("GET", "/old")
+            .with_status(301)
+            .with_header("location", "/moved")
+            .create_async()
+            .await;
+        let _moved = server
+            .mock("GET", "/moved")
+            .with_status(302)
+            .with_header("location", "/elsewhere")
+            .create_async()
+            .await;
+        let _elsewhere = server
+            .mock("GET", "/elsewhere")
+            .with_status(200)
+            .with_body(SAMPLE_RSS)
+            .create_async()
+            .await;
+
+        // Only the permanent part of the chain counts
+        let (feed, _) = fetch(&format!("{}/old", server.url())).await;
+        assert_eq!(feed.moved_to, Some(format!("{}/moved", server.url())));
+
+        let (feed, _) = fetch(&format!("{}/moved", server.url())).await;
+        assert_eq!(feed.moved_to, None);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_redirect_loops() {
+        let mut server = mockito::Server::new_async().await;
+        let _loop = server
+            .mock("GET", "/loop")
+            .with_status(301)
+            .with_header("location", "/loop")
+            .create_async()
+            .await;
+
+        let result = FeedFetcher::new()
+            .unwrap()
+            .fetch(&format!("{}/loop", server.url()), &CacheValidators::default())
+            .await;
 
         assert!(matches!(result, Err(Error::Internal(_))));
     }
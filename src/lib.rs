@@ -1,8 +1,21 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::future::Future;
 use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use futures_util::future::join_all;
+use tokio::sync::Semaphore;
+
+use crate::config::Config;
+use crate::rules::{MatchKind, Rule, RuleAction, RuleField};
+
+pub mod config;
+pub mod dedup;
 pub mod ffi;
 pub mod http;
+pub mod opml;
+pub mod rules;
 pub mod sqlite;
 
 #[derive(Clone)]
@@ -14,16 +27,147 @@ pub struct Feed {
     pub last_synced_at: Option<u64>,
     pub created_at: u64,
     pub updated_at: u64,
+    pub validators: CacheValidators,
+    /// When set, new entries from this feed wait for approval before showing
+    /// up in the timeline.
+    pub require_approval: bool,
+    /// The number of approved entries not yet marked read.
+    pub unread_count: u64,
+    /// The names of the folders the feed is in, sorted.
+    pub folders: Vec<String>,
+    /// The URL the feed had before it last permanently moved.
+    pub previous_url: Option<String>,
+    /// Paused feeds are skipped by sync_all. Feeds that answer 410 Gone are
+    /// paused automatically.
+    pub paused: bool,
+    /// When the feed is backing off after failing, or the server asked to be
+    /// left alone, sync_all skips the feed until this time.
+    pub next_fetch_at: Option<u64>,
+    /// How many syncs in a row have failed. Reset by a successful sync.
+    pub consecutive_failures: u64,
+    /// Why the last sync failed, if it did.
+    pub last_error: Option<String>,
+    /// The feed's own retention policy, used instead of the global one.
+    pub retention: Option<RetentionPolicy>,
 }
 
-#[allow(async_fn_in_trait)]
+/// RetentionPolicy bounds how many of a feed's entries are kept. Starred
+/// entries and entries waiting for approval are always kept. Limits left as
+/// None don't prune anything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Entries published longer ago than this many days are pruned. Entries
+    /// without a publish time are aged from when they were stored.
+    pub max_age_days: Option<u64>,
+    /// Only this many of the feed's newest entries are kept.
+    pub max_entries: Option<u64>,
+    /// Keep unread entries, however old they are.
+    pub keep_unread: bool,
+}
+
+impl RetentionPolicy {
+    /// Whether the policy prunes anything at all.
+    pub fn is_unbounded(&self) -> bool {
+        self.max_age_days.is_none() && self.max_entries.is_none()
+    }
+}
+
+/// Folder is a named group of feeds. A feed can be in any number of folders.
+#[derive(Clone)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+    pub feed_count: u64,
+}
+
+/// Storage is the persistence surface for feeds and their entries.
+///
+/// Its methods are deliberately synchronous: Core serializes every call behind
+/// a lock and must never hold that lock across an await point.
 pub trait Storage {
     fn list_feeds(&self) -> Result<Vec<Feed>, Error>;
-    async fn add_feed(&self, url: String) -> Result<Feed, Error>;
+    fn add_feed(&self, url: String) -> Result<Feed, Error>;
     fn get_feed(&self, id: &str) -> Result<Feed, Error>;
     fn list_entries(&self, feed_id: &str, fetch_all: bool) -> Result<Vec<FeedEntry>, Error>;
-    fn list_timeline(&self) -> Result<Vec<(String, FeedEntry)>, Error>;
-    fn update_feed(&self, feed_id: &str, remote: &RemoteFeed, entries: &[RemoteEntry]) -> Result<(), Error>;
+    /// Lists one page of entries across all feeds matching the query, newest
+    /// first, each paired with the name of its feed.
+    fn list_timeline(&self, query: &TimelineQuery) -> Result<TimelinePage, Error>;
+    /// Lists entries waiting for approval, optionally limited to one feed.
+    fn list_pending(&self, feed_id: Option<&str>) -> Result<Vec<(String, FeedEntry)>, Error>;
+    /// Approves or rejects entries. Fails with NotFound, changing nothing, if any
+    /// of the IDs don't exist.
+    fn set_entries_approved(&self, entry_ids: &[String], approved: bool) -> Result<usize, Error>;
+    fn set_feed_require_approval(&self, feed_id: &str, require_approval: bool) -> Result<(), Error>;
+    fn set_feed_paused(&self, feed_id: &str, paused: bool) -> Result<(), Error>;
+    /// Sets the earliest time the feed is fetched again, or None to fetch it
+    /// on the next sync. A successful sync clears it.
+    fn set_feed_next_fetch(&self, feed_id: &str, next_fetch_at: Option<u64>) -> Result<(), Error>;
+    /// Records a failed sync: counts it towards the consecutive failures, keeps
+    /// the error and sets the earliest time the feed is fetched again.
+    fn record_feed_failure(&self, feed_id: &str, error: &str, next_fetch_at: Option<u64>) -> Result<(), Error>;
+    fn set_entry_read(&self, entry_id: &str, read: bool) -> Result<(), Error>;
+    fn set_entry_starred(&self, entry_id: &str, starred: bool) -> Result<(), Error>;
+    /// Lists starred entries across all feeds, newest first, paired with the
+    /// name of their feed.
+    fn list_starred(&self) -> Result<Vec<(String, FeedEntry)>, Error>;
+    /// Sets the read state of every entry in a feed, returning how many changed.
+    fn set_feed_read(&self, feed_id: &str, read: bool) -> Result<usize, Error>;
+    /// Marks every entry read, returning how many changed.
+    fn mark_all_read(&self) -> Result<usize, Error>;
+    fn get_entry(&self, id: &str) -> Result<FeedEntry, Error>;
+    /// Searches the title and description of approved entries, best match first.
+    /// The query uses SQLite FTS5 syntax; one that doesn't parse fails with
+    /// InvalidArgument.
+    fn search_entries(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchResult>, Error>;
+    /// Updates the feed's details and inserts any entries not seen before,
+    /// returning the IDs of the new entries. Entries seen before whose title,
+    /// description, link or publish time changed are updated, keeping what they
+    /// were before as a revision.
+    fn update_feed(&self, feed_id: &str, remote: &RemoteFeed, entries: &[RemoteEntry]) -> Result<Vec<String>, Error>;
+    /// Lists the earlier versions of an entry, newest first.
+    fn list_revisions(&self, entry_id: &str) -> Result<Vec<EntryRevision>, Error>;
+    fn add_rule(&self, field: RuleField, match_kind: MatchKind, pattern: &str, action: &RuleAction) -> Result<Rule, Error>;
+    /// Lists rules in the order they are applied.
+    fn list_rules(&self) -> Result<Vec<Rule>, Error>;
+    fn remove_rule(&self, id: &str) -> Result<(), Error>;
+    fn tag_entry(&self, entry_id: &str, tag: &str) -> Result<(), Error>;
+    /// Adds a folder. Names are unique, ignoring case; a taken name fails with
+    /// AlreadyExists.
+    fn add_folder(&self, name: &str) -> Result<Folder, Error>;
+    /// Lists folders sorted by name.
+    fn list_folders(&self) -> Result<Vec<Folder>, Error>;
+    fn rename_folder(&self, id: &str, name: &str) -> Result<(), Error>;
+    /// Removes a folder. The feeds in it are kept.
+    fn remove_folder(&self, id: &str) -> Result<(), Error>;
+    /// Puts a feed in a folder. Doing so again is a no-op.
+    fn add_feed_to_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error>;
+    fn remove_feed_from_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error>;
+    /// Points a feed at the URL it permanently moved to, keeping the old one as
+    /// its previous URL. If another feed already has that URL, the feed is
+    /// merged into it instead, keeping the stricter settings of the two and the
+    /// read and starred state of entries both have. Returns the ID of the feed
+    /// that ends up with the URL.
+    fn move_feed(&self, feed_id: &str, url: &str) -> Result<String, Error>;
+    /// Removes a feed along with all of its entries.
+    fn remove_feed(&self, id: &str) -> Result<(), Error>;
+    /// Records a sync that found nothing new, e.g. a 304 from the server. Like
+    /// update_feed, this clears the feed's failures.
+    fn mark_feed_synced(&self, feed_id: &str) -> Result<(), Error>;
+    /// Gets the retention policy of feeds that don't have their own.
+    fn get_retention(&self) -> Result<RetentionPolicy, Error>;
+    fn set_retention(&self, policy: &RetentionPolicy) -> Result<(), Error>;
+    /// Gives a feed its own retention policy, or None to go back to the global
+    /// one.
+    fn set_feed_retention(&self, feed_id: &str, policy: Option<&RetentionPolicy>) -> Result<(), Error>;
+    /// Deletes the entries each feed's retention policy, or the global one for
+    /// feeds without their own, doesn't keep. With dry_run they are only
+    /// counted. Pruned entries are remembered so that syncing doesn't bring
+    /// them back, until their feed stops listing them. Returns a result per
+    /// feed that had any.
+    fn prune_entries(&self, global: &RetentionPolicy, dry_run: bool) -> Result<Vec<FeedPruneResult>, Error>;
+    /// Rebuilds the database file to give the space freed by pruning back.
+    fn vacuum(&self) -> Result<(), Error>;
 }
 
 /// FeedEntry is the representation of a post from a feed.
@@ -38,6 +182,116 @@ pub struct FeedEntry {
     pub created_at: u64,
     pub publish_time: Option<u64>,
     pub approved: bool,
+    pub read: bool,
+    /// Starred entries are kept until unstarred and can be listed on their own.
+    pub starred: bool,
+    /// Rejected entries stay stored, unapproved, so that syncing doesn't bring
+    /// them back as pending.
+    pub rejected: bool,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+    /// The ID of the entry from another feed that this one is a copy of, as
+    /// told by dedup.
+    pub duplicate_of: Option<String>,
+    /// When the feed last changed the entry after it was first stored. What it
+    /// was before is kept as revisions.
+    pub updated_at: Option<u64>,
+}
+
+/// EntryRevision is what an entry looked like before the feed changed it.
+#[derive(Clone)]
+pub struct EntryRevision {
+    pub id: String,
+    pub entry_id: String,
+    pub title: String,
+    pub description: String,
+    pub link: String,
+    pub publish_time: Option<u64>,
+    /// When the feed replaced this content.
+    pub replaced_at: u64,
+}
+
+/// TimelineQuery selects a page of the timeline. Filters left as None don't
+/// filter anything; by default only approved entries are listed.
+#[derive(Debug, Clone)]
+pub struct TimelineQuery {
+    /// The next_cursor of the previous page, or None for the first page.
+    pub cursor: Option<String>,
+    pub limit: usize,
+    /// Only list entries published at or after this time. Entries without a
+    /// publish time are compared by when they were stored.
+    pub since: Option<u64>,
+    /// Only list entries published before this time.
+    pub until: Option<u64>,
+    /// Only list entries from these feeds; empty lists every feed.
+    pub feed_ids: Vec<String>,
+    /// Only list entries from feeds in this folder.
+    pub folder_id: Option<String>,
+    pub read: Option<bool>,
+    pub starred: Option<bool>,
+    pub approved: Option<bool>,
+    /// Leave out entries that are copies of an entry that is listed too, or
+    /// would be but for the read, starred and date filters.
+    pub collapse_duplicates: bool,
+}
+
+impl Default for TimelineQuery {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            limit: 100,
+            since: None,
+            until: None,
+            feed_ids: vec![],
+            folder_id: None,
+            read: None,
+            starred: None,
+            approved: Some(true),
+            collapse_duplicates: false,
+        }
+    }
+}
+
+/// TimelinePage is one page of the timeline.
+pub struct TimelinePage {
+    pub entries: Vec<(String, FeedEntry)>,
+    /// Pass this as the cursor of the next query to continue where this page
+    /// ended. None when there are no more entries.
+    pub next_cursor: Option<String>,
+}
+
+/// SearchFilters narrow a search. Fields left unset don't filter anything.
+#[derive(Debug, Clone)]
+pub struct SearchFilters {
+    pub feed_id: Option<String>,
+    /// Only match entries published at or after this time. Entries without a
+    /// publish time are compared by when they were stored.
+    pub since: Option<u64>,
+    /// Only match entries published before this time.
+    pub until: Option<u64>,
+    pub unread_only: bool,
+    pub limit: usize,
+}
+
+impl Default for SearchFilters {
+    fn default() -> Self {
+        Self {
+            feed_id: None,
+            since: None,
+            until: None,
+            unread_only: false,
+            limit: 50,
+        }
+    }
+}
+
+/// SearchResult is an entry matched by a search.
+pub struct SearchResult {
+    pub feed_name: String,
+    pub entry: FeedEntry,
+    /// A short excerpt around the match, with each matched term wrapped in
+    /// square brackets.
+    pub snippet: String,
 }
 
 /// RemoteFeed is the representation of the feed's details from the server.
@@ -45,6 +299,22 @@ pub struct RemoteFeed {
     pub url: String,
     pub title: String,
     pub description: String,
+    /// Describes each item in the document that was dropped because it could not
+    /// be turned into an entry.
+    pub skipped_entries: Vec<String>,
+    pub validators: CacheValidators,
+    /// Where the feed permanently moved to, when the fetch followed permanent
+    /// redirects to get to it.
+    pub moved_to: Option<String>,
+}
+
+/// CacheValidators are the HTTP validators a server sent along with a feed. They
+/// are sent back on the next fetch so an unchanged feed can be answered with a
+/// 304 instead of the full body.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -54,18 +324,167 @@ pub struct RemoteEntry {
     pub guid: String,
     pub link: String,
     pub publish_time_unix_secs: Option<u64>,
+    pub author: Option<String>,
+}
+
+/// FetchResult is the outcome of a successful fetch.
+pub enum FetchResult {
+    /// The feed was downloaded and parsed.
+    Fetched(RemoteFeed, Vec<RemoteEntry>),
+    /// The URL was a web page rather than a feed. These are the feeds it links
+    /// to, if any.
+    Discovered(Vec<FeedCandidate>),
+    /// The server reported that the feed hasn't changed since the validators
+    /// passed to the fetch were issued. moved_to is as for RemoteFeed.
+    NotModified { moved_to: Option<String> },
+}
+
+/// FeedCandidate is a feed a web page links to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedCandidate {
+    /// The feed's absolute URL.
+    pub url: String,
+    pub title: Option<String>,
+    /// The MIME type the page gave for the feed, e.g. application/atom+xml.
+    pub content_type: String,
 }
 
 /// Fetcher is surface for taking a url and fetching the feed and its entries.
 #[allow(async_fn_in_trait)]
 pub trait Fetcher {
-    async fn fetch(&self, url: &str) -> Result<(RemoteFeed, Vec<RemoteEntry>), Error>;
+    async fn fetch(&self, url: &str, validators: &CacheValidators) -> Result<FetchResult, Error>;
+}
+
+/// SyncReport is the outcome of syncing every feed, one result per feed that
+/// was fetched.
+pub struct SyncReport {
+    pub feeds: Vec<FeedSyncResult>,
+    /// How many feeds were skipped for being paused.
+    pub paused: usize,
+    /// How many feeds were skipped because they're backing off after failing
+    /// or their server asked for a break.
+    pub deferred: usize,
+    /// How many entries were pruned after syncing.
+    pub pruned: usize,
+    /// Why pruning failed, if it did. The feeds were synced all the same.
+    pub prune_error: Option<Error>,
+}
+
+impl SyncReport {
+    pub fn failed(&self) -> usize {
+        self.feeds.iter().filter(|f| f.error.is_some()).count()
+    }
+}
+
+/// PruneReport is the outcome of pruning, one result per feed that had entries
+/// to prune.
+pub struct PruneReport {
+    pub feeds: Vec<FeedPruneResult>,
+    /// Set when nothing was deleted, only counted.
+    pub dry_run: bool,
+}
+
+impl PruneReport {
+    pub fn entries(&self) -> usize {
+        self.feeds.iter().map(|f| f.entries).sum()
+    }
+}
+
+/// FeedPruneResult is how many of a feed's entries were, or with a dry run
+/// would be, pruned.
+pub struct FeedPruneResult {
+    pub feed_id: String,
+    pub url: String,
+    pub entries: usize,
+}
+
+/// ImportReport is the outcome of importing an OPML document, one result per
+/// feed listed in it.
+pub struct ImportReport {
+    pub feeds: Vec<FeedImportResult>,
+}
+
+impl ImportReport {
+    pub fn added(&self) -> usize {
+        self.feeds.iter().filter(|f| f.error.is_none()).count()
+    }
+
+    /// Counts feeds that were skipped because they were already subscribed to.
+    pub fn skipped(&self) -> usize {
+        self.feeds
+            .iter()
+            .filter(|f| matches!(f.error, Some(Error::AlreadyExists)))
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.feeds.len() - self.added() - self.skipped()
+    }
+}
+
+/// FeedImportResult is the outcome of importing a single feed. The feed was
+/// added when error is None, and skipped when it's AlreadyExists.
+pub struct FeedImportResult {
+    pub url: String,
+    /// The title given in the OPML document.
+    pub title: Option<String>,
+    /// The folder the feed was listed in.
+    pub folder: Option<String>,
+    /// The feed added, or the one already subscribed to when skipped.
+    pub feed_id: Option<String>,
+    pub error: Option<Error>,
+    /// Set when the feed couldn't be put in its folder.
+    pub folder_error: Option<Error>,
+}
+
+/// FeedSyncResult is the outcome of syncing a single feed. A sync succeeded when
+/// error is None.
+pub struct FeedSyncResult {
+    /// The feed synced, or the one it was merged into after moving to a URL
+    /// already subscribed to.
+    pub feed_id: String,
+    pub url: String,
+    pub new_entries: usize,
+    /// Items in the document that couldn't be turned into entries.
+    pub skipped_entries: Vec<String>,
+    /// The URL the feed was found to have permanently moved to.
+    pub moved_to: Option<String>,
+    /// Set when the feed was paused because the server said it's gone.
+    pub paused: bool,
+    /// Set when the sync failed; the feed is skipped until then.
+    pub retry_at: Option<u64>,
+    /// How many times the fetch was retried after transient failures.
+    pub retries: usize,
+    pub duration: Duration,
+    pub error: Option<Error>,
 }
 
 #[derive(Debug)]
 pub enum Error {
     NotFound,
     AlreadyExists,
+    InvalidArgument(String),
+    /// The URL given for a new feed was a web page linking to several feeds.
+    /// One of them has to be picked and added instead.
+    MultipleFeeds(Vec<FeedCandidate>),
+    /// The server answered with an error status. retry_after holds the delay,
+    /// in seconds, it asked for in a Retry-After header.
+    Http { status: u16, retry_after: Option<u64> },
+    /// The document couldn't be parsed as a feed. The location is 1-based, and
+    /// known only for syntax errors.
+    Parse {
+        message: String,
+        line: Option<u64>,
+        column: Option<u64>,
+    },
+    /// The server couldn't be reached, e.g. it didn't resolve or refused the
+    /// connection.
+    Network(String),
+    Timeout,
+    /// The TLS handshake failed, e.g. on an invalid certificate.
+    Tls(String),
+    /// The response body was larger than the limit, in bytes.
+    TooLarge { limit: u64 },
     Io(std::io::Error),
     Internal(String),
 }
@@ -75,6 +494,34 @@ impl fmt::Display for Error {
         match self {
             Error::NotFound => write!(f, "not found"),
             Error::AlreadyExists => write!(f, "feed already exists"),
+            Error::InvalidArgument(msg) => write!(f, "invalid argument: {msg}"),
+            Error::MultipleFeeds(candidates) => {
+                write!(f, "the page links to {} feeds; pick one", candidates.len())
+            }
+            Error::Http { status, retry_after } => {
+                write!(f, "HTTP {status}")?;
+                let reason = reqwest::StatusCode::from_u16(*status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason());
+                if let Some(reason) = reason {
+                    write!(f, " {reason}")?;
+                }
+                if let Some(seconds) = retry_after {
+                    write!(f, " (retry after {seconds}s)")?;
+                }
+                Ok(())
+            }
+            Error::Parse { message, line: Some(line), column: Some(column) } => {
+                write!(f, "parse error at line {line}, column {column}: {message}")
+            }
+            Error::Parse { message, line: Some(line), column: None } => {
+                write!(f, "parse error at line {line}: {message}")
+            }
+            Error::Parse { message, .. } => write!(f, "parse error: {message}"),
+            Error::Network(msg) => write!(f, "network error: {msg}"),
+            Error::Timeout => write!(f, "timed out"),
+            Error::Tls(msg) => write!(f, "TLS error: {msg}"),
+            Error::TooLarge { limit } => write!(f, "response is larger than {limit} bytes"),
             Error::Io(err) => write!(f, "{err}"),
             Error::Internal(msg) => write!(f, "internal error: {msg}"),
         }
@@ -85,7 +532,17 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::Io(err) => Some(err),
-            Error::NotFound | Error::AlreadyExists | Error::Internal(_) => None,
+            Error::NotFound
+            | Error::AlreadyExists
+            | Error::InvalidArgument(_)
+            | Error::MultipleFeeds(_)
+            | Error::Http { .. }
+            | Error::Parse { .. }
+            | Error::Network(_)
+            | Error::Timeout
+            | Error::Tls(_)
+            | Error::TooLarge { .. }
+            | Error::Internal(_) => None,
         }
     }
 }
@@ -102,12 +559,44 @@ impl From<rusqlite::Error> for Error {
     }
 }
 
+/// SyncOptions bound how many feeds sync_all fetches at once, overall and
+/// against any single host, and how it retries fetches that fail with a
+/// transient error.
+#[derive(Debug, Clone)]
+pub struct SyncOptions {
+    pub max_concurrent: usize,
+    pub max_per_host: usize,
+    pub max_retries: usize,
+    /// The delay before the first retry. It doubles for each one after, up to
+    /// a minute, and is jittered so feeds on the same host don't retry in
+    /// lockstep.
+    pub retry_delay: Duration,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            max_per_host: 2,
+            max_retries: 2,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
 /// Core is the top-level service object, generic over a storage and fetcher
 /// implementation. Use concrete type aliases or wrappers (e.g. FFICore) for
 /// FFI boundaries.
+///
+/// The store sits behind a mutex that is only ever held for the duration of a
+/// single synchronous Storage call. Fetches happen outside of it, so they can
+/// run concurrently while writes to the store stay serialized.
 pub struct Core<S, F> {
     store: Mutex<S>,
     fetcher: F,
+    sync_options: SyncOptions,
+    /// The global retention policy when it's configured rather than stored.
+    retention: Option<RetentionPolicy>,
 }
 
 impl<S: Storage, F: Fetcher> Core<S, F> {
@@ -115,47 +604,982 @@ impl<S: Storage, F: Fetcher> Core<S, F> {
         Self {
             store: Mutex::new(store),
             fetcher,
+            sync_options: SyncOptions::default(),
+            retention: None,
         }
     }
 
+    pub fn with_sync_options(mut self, sync_options: SyncOptions) -> Self {
+        self.sync_options = sync_options;
+        self
+    }
+
+    /// Applies the sync options and retention policy of a config. A configured
+    /// retention policy takes the place of the stored global one.
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.sync_options = config.sync_options();
+        self.retention = config.retention.value.clone();
+        self
+    }
+
     pub fn list_feeds(&self) -> Result<Vec<Feed>, Error> {
         self.store.lock().unwrap().list_feeds()
     }
 
+    /// Adds a feed and stores its current entries. When the URL is a web page
+    /// linking to a single feed, that feed is added instead; when it links to
+    /// several, this fails with MultipleFeeds listing them.
     pub async fn add_feed(&self, url: String) -> Result<Feed, Error> {
-        let (remote_feed, remote_entries) = self.fetcher.fetch(&url).await?;
+        let (url, remote_feed, remote_entries) =
+            match self.fetcher.fetch(&url, &CacheValidators::default()).await? {
+                FetchResult::Fetched(feed, entries) => {
+                    (feed.moved_to.clone().unwrap_or(url), feed, entries)
+                }
+                FetchResult::Discovered(mut candidates) => {
+                    if candidates.len() > 1 {
+                        return Err(Error::MultipleFeeds(candidates));
+                    }
+                    let Some(candidate) = candidates.pop() else {
+                        return Err(Error::InvalidArgument(format!(
+                            "{url} is a web page that doesn't link to a feed"
+                        )));
+                    };
+                    // Only follow one level of discovery
+                    match self
+                        .fetcher
+                        .fetch(&candidate.url, &CacheValidators::default())
+                        .await?
+                    {
+                        FetchResult::Fetched(feed, entries) => {
+                            (feed.moved_to.clone().unwrap_or(candidate.url), feed, entries)
+                        }
+                        _ => {
+                            return Err(Error::Internal(format!(
+                                "{} is linked to as a feed but isn't one",
+                                candidate.url
+                            )))
+                        }
+                    }
+                }
+                FetchResult::NotModified { .. } => {
+                    return Err(Error::Internal(
+                        "server answered an unconditional request with 304".to_string(),
+                    ))
+                }
+            };
 
-        let feed = self.store.lock().unwrap().add_feed(url).await?;
-
-        self.store
-            .lock()
-            .unwrap()
-            .update_feed(&feed.id, &remote_feed, &remote_entries)?;
+        let store = self.store.lock().unwrap();
+        let feed = store.add_feed(url)?;
+        ingest(&*store, &feed.id, &remote_feed, &remote_entries)?;
 
         Ok(feed)
     }
 
-    pub async fn sync_all(&self) -> Result<(), Error> {
+    /// Syncs every feed, fetching up to the configured number of feeds at once,
+    /// then prunes entries as the retention policies say. A feed failing doesn't
+    /// stop the others from being attempted; its error is recorded in the report
+    /// instead. Results are in the order of list_feeds.
+    pub async fn sync_all(&self) -> Result<SyncReport, Error> {
+        let mut feeds = self.store.lock().unwrap().list_feeds()?;
+        let total = feeds.len();
+        feeds.retain(|f| !f.paused);
+        let paused = total - feeds.len();
+        let now = unix_now();
+        feeds.retain(|f| f.next_fetch_at.is_none_or(|at| at <= now));
+        let deferred = total - paused - feeds.len();
+
+        let urls: Vec<&str> = feeds.iter().map(|f| f.url.as_str()).collect();
+        let results = self
+            .fetch_limited(&urls, |i| {
+                let feed = &feeds[i];
+                async move {
+                    let started = Instant::now();
+                    let mut result = FeedSyncResult {
+                        feed_id: feed.id.clone(),
+                        url: feed.url.clone(),
+                        new_entries: 0,
+                        skipped_entries: vec![],
+                        moved_to: None,
+                        paused: false,
+                        retry_at: None,
+                        retries: 0,
+                        duration: Duration::ZERO,
+                        error: None,
+                    };
+                    if let Err(err) = self.sync_feed(feed, &mut result).await {
+                        result.error = Some(err);
+                    }
+                    result.duration = started.elapsed();
+                    result
+                }
+            })
+            .await;
+
+        let (pruned, prune_error) = match self.prune(false, false) {
+            Ok(report) => (report.entries(), None),
+            Err(err) => (0, Some(err)),
+        };
+        Ok(SyncReport {
+            feeds: results,
+            paused,
+            deferred,
+            pruned,
+            prune_error,
+        })
+    }
+
+    /// Runs task(i) for each URL, holding tasks back so that no more run at once
+    /// than the sync options allow, overall or against any single host. Results
+    /// are in the order of urls.
+    async fn fetch_limited<T, Fut>(&self, urls: &[&str], task: impl Fn(usize) -> Fut) -> Vec<T>
+    where
+        Fut: Future<Output = T>,
+    {
+        let global = Semaphore::new(self.sync_options.max_concurrent.max(1));
+        let mut hosts: HashMap<String, Semaphore> = HashMap::new();
+        for url in urls {
+            hosts
+                .entry(host_key(url))
+                .or_insert_with(|| Semaphore::new(self.sync_options.max_per_host.max(1)));
+        }
+
+        join_all(urls.iter().enumerate().map(|(i, url)| {
+            let hosts = &hosts;
+            let global = &global;
+            let task = &task;
+            async move {
+                // Take the per-host permit first so a busy host doesn't sit on
+                // global permits that other hosts could be using.
+                let _host_permit = hosts[&host_key(url)].acquire().await;
+                let _permit = global.acquire().await;
+                task(i).await
+            }
+        }))
+        .await
+    }
+
+    /// Adds every feed listed in an OPML document, fetching them like sync_all
+    /// does. Feeds that are already subscribed to, or listed twice, are reported
+    /// with AlreadyExists and not fetched, but still put in their folders.
+    pub async fn import_opml(&self, xml: &str) -> Result<ImportReport, Error> {
+        let listed = opml::parse(xml)?;
+        let (mut feed_ids, mut folders) = {
+            let store = self.store.lock().unwrap();
+            let feed_ids: HashMap<String, String> =
+                store.list_feeds()?.into_iter().map(|f| (f.url, f.id)).collect();
+            let folders: HashMap<String, String> = store
+                .list_folders()?
+                .into_iter()
+                .map(|f| (f.name.to_ascii_lowercase(), f.id))
+                .collect();
+            (feed_ids, folders)
+        };
+        let mut seen: HashSet<&str> = feed_ids.keys().map(String::as_str).collect();
+        let duplicate: Vec<bool> = listed.iter().map(|f| !seen.insert(&f.url)).collect();
+
+        let urls: Vec<&str> = listed.iter().map(|f| f.url.as_str()).collect();
+        let mut results = self
+            .fetch_limited(&urls, |i| {
+                let listed = &listed[i];
+                let duplicate = duplicate[i];
+                async move {
+                    let added = if duplicate {
+                        Err(Error::AlreadyExists)
+                    } else {
+                        self.add_feed(listed.url.clone()).await
+                    };
+                    FeedImportResult {
+                        url: listed.url.clone(),
+                        title: listed.title.clone(),
+                        folder: listed.folder.clone(),
+                        feed_id: added.as_ref().ok().map(|f| f.id.clone()),
+                        error: added.err(),
+                        folder_error: None,
+                    }
+                }
+            })
+            .await;
+
+        // Outlines that held feeds become folders, reusing existing folders of
+        // the same name. A feed that can't be put in its folder is still added.
+        let store = self.store.lock().unwrap();
+        for result in &mut results {
+            match (&result.feed_id, &result.error) {
+                (Some(feed_id), _) => {
+                    feed_ids.insert(result.url.clone(), feed_id.clone());
+                }
+                (None, Some(Error::AlreadyExists)) => result.feed_id = feed_ids.get(&result.url).cloned(),
+                _ => {}
+            }
+            let (Some(feed_id), Some(folder)) = (&result.feed_id, &result.folder) else {
+                continue;
+            };
+            let folder_id = match folders.get(&folder.to_ascii_lowercase()) {
+                Some(id) => Ok(id.clone()),
+                None => store.add_folder(folder).map(|f| {
+                    folders.insert(folder.to_ascii_lowercase(), f.id.clone());
+                    f.id
+                }),
+            };
+            if let Err(err) = folder_id.and_then(|folder_id| store.add_feed_to_folder(feed_id, &folder_id)) {
+                result.folder_error = Some(err);
+            }
+        }
+
+        Ok(ImportReport { feeds: results })
+    }
+
+    /// Renders every feed as an OPML document.
+    pub fn export_opml(&self) -> Result<String, Error> {
         let feeds = self.store.lock().unwrap().list_feeds()?;
-        for feed in feeds {
-            let (remote_feed, remote_entries) = self.fetcher.fetch(&feed.url).await?;
-            self.store
-                .lock()
-                .unwrap()
-                .update_feed(&feed.id, &remote_feed, &remote_entries)?;
+        Ok(opml::render(&feeds))
+    }
+
+    /// Syncs a feed, recording a failure against it if that doesn't work out.
+    /// A feed merged into another before failing has the failure recorded
+    /// against the one that's left.
+    async fn sync_feed(&self, feed: &Feed, result: &mut FeedSyncResult) -> Result<(), Error> {
+        let Err(err) = self.fetch_and_ingest(feed, result).await else {
+            return Ok(());
+        };
+        let merged;
+        let feed = if result.feed_id == feed.id {
+            feed
+        } else {
+            merged = self.get_feed(&result.feed_id)?;
+            &merged
+        };
+        self.record_failure(feed, &err, result)?;
+        Err(err)
+    }
+
+    async fn fetch_and_ingest(&self, feed: &Feed, result: &mut FeedSyncResult) -> Result<(), Error> {
+        let fetched = self.fetch_with_retries(feed, result).await?;
+
+        let store = self.store.lock().unwrap();
+        let moved_to = match &fetched {
+            FetchResult::Fetched(remote_feed, _) => remote_feed.moved_to.clone(),
+            FetchResult::NotModified { moved_to } => moved_to.clone(),
+            FetchResult::Discovered(_) => None,
+        };
+        // A feed merged into one already at the new URL syncs into that one
+        let feed_id = match &moved_to {
+            Some(url) => store.move_feed(&feed.id, url)?,
+            None => feed.id.clone(),
+        };
+        result.feed_id = feed_id.clone();
+        result.moved_to = moved_to;
+
+        match fetched {
+            FetchResult::Fetched(remote_feed, remote_entries) => {
+                result.new_entries = ingest(&*store, &feed_id, &remote_feed, &remote_entries)?;
+                result.skipped_entries = remote_feed.skipped_entries;
+            }
+            FetchResult::NotModified { .. } => store.mark_feed_synced(&feed_id)?,
+            FetchResult::Discovered(_) => {
+                return Err(Error::Internal("the feed URL now serves a web page".to_string()))
+            }
         }
         Ok(())
     }
 
+    /// Fetches a feed, retrying network errors, timeouts and server errors up to
+    /// max_retries times.
+    async fn fetch_with_retries(&self, feed: &Feed, result: &mut FeedSyncResult) -> Result<FetchResult, Error> {
+        loop {
+            match self.fetcher.fetch(&feed.url, &feed.validators).await {
+                Err(err) if is_transient(&err) && result.retries < self.sync_options.max_retries => {
+                    let delay = self
+                        .sync_options
+                        .retry_delay
+                        .saturating_mul(2u32.pow(result.retries.min(16) as u32))
+                        .min(MAX_RETRY_DELAY);
+                    tokio::time::sleep(jitter(delay)).await;
+                    result.retries += 1;
+                }
+                fetched => return fetched,
+            }
+        }
+    }
+
+    /// Backs off from a feed that failed to sync, exponentially in the number
+    /// of failures in a row and for at least as long as the server asked. Feeds
+    /// that are gone for good are paused instead.
+    fn record_failure(&self, feed: &Feed, err: &Error, result: &mut FeedSyncResult) -> Result<(), Error> {
+        let backoff = backoff(feed.consecutive_failures + 1);
+        let wait = match *err {
+            Error::Http { status: 410, .. } => None,
+            Error::Http { status: 429, retry_after } => {
+                Some(backoff.max(retry_after.unwrap_or(DEFAULT_RETRY_AFTER).min(MAX_WAIT)))
+            }
+            Error::Http { status: 503, retry_after: Some(retry_after) } => {
+                Some(backoff.max(retry_after.min(MAX_WAIT)))
+            }
+            _ => Some(backoff),
+        };
+        let retry_at = wait.map(|wait| unix_now() + wait);
+
+        let store = self.store.lock().unwrap();
+        if wait.is_none() {
+            store.set_feed_paused(&feed.id, true)?;
+            result.paused = true;
+        }
+        store.record_feed_failure(&feed.id, &err.to_string(), retry_at)?;
+        result.retry_at = retry_at;
+        Ok(())
+    }
+
     pub fn get_feed(&self, id: &str) -> Result<Feed, Error> {
         self.store.lock().unwrap().get_feed(id)
     }
 
+    pub fn remove_feed(&self, id: &str) -> Result<(), Error> {
+        self.store.lock().unwrap().remove_feed(id)
+    }
+
     pub fn list_entries(&self, feed_id: &str, fetch_all: bool) -> Result<Vec<FeedEntry>, Error> {
         self.store.lock().unwrap().list_entries(feed_id, fetch_all)
     }
 
-    pub fn list_timeline(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
-        self.store.lock().unwrap().list_timeline()
+    pub fn list_revisions(&self, entry_id: &str) -> Result<Vec<EntryRevision>, Error> {
+        self.store.lock().unwrap().list_revisions(entry_id)
+    }
+
+    pub fn list_timeline(&self, query: &TimelineQuery) -> Result<TimelinePage, Error> {
+        if query.limit == 0 {
+            return Err(Error::InvalidArgument("limit must be at least 1".into()));
+        }
+        self.store.lock().unwrap().list_timeline(query)
+    }
+
+    /// Adds a rule, validating its pattern. The rule applies to entries ingested
+    /// from now on; use apply_rules to run it over existing entries.
+    pub fn add_rule(
+        &self,
+        field: RuleField,
+        match_kind: MatchKind,
+        pattern: &str,
+        action: &RuleAction,
+    ) -> Result<Rule, Error> {
+        rules::validate(match_kind, pattern)?;
+        self.store
+            .lock()
+            .unwrap()
+            .add_rule(field, match_kind, pattern, action)
+    }
+
+    pub fn list_rules(&self) -> Result<Vec<Rule>, Error> {
+        self.store.lock().unwrap().list_rules()
+    }
+
+    pub fn remove_rule(&self, id: &str) -> Result<(), Error> {
+        self.store.lock().unwrap().remove_rule(id)
+    }
+
+    pub fn add_folder(&self, name: &str) -> Result<Folder, Error> {
+        self.store.lock().unwrap().add_folder(validate_folder_name(name)?)
+    }
+
+    pub fn list_folders(&self) -> Result<Vec<Folder>, Error> {
+        self.store.lock().unwrap().list_folders()
+    }
+
+    /// Finds a folder by its ID or, ignoring case, its name.
+    pub fn find_folder(&self, id_or_name: &str) -> Result<Folder, Error> {
+        self.store
+            .lock()
+            .unwrap()
+            .list_folders()?
+            .into_iter()
+            .find(|f| f.id == id_or_name || f.name.eq_ignore_ascii_case(id_or_name))
+            .ok_or(Error::NotFound)
+    }
+
+    pub fn rename_folder(&self, id: &str, name: &str) -> Result<(), Error> {
+        self.store
+            .lock()
+            .unwrap()
+            .rename_folder(id, validate_folder_name(name)?)
+    }
+
+    pub fn remove_folder(&self, id: &str) -> Result<(), Error> {
+        self.store.lock().unwrap().remove_folder(id)
+    }
+
+    pub fn add_feed_to_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error> {
+        self.store.lock().unwrap().add_feed_to_folder(feed_id, folder_id)
+    }
+
+    pub fn remove_feed_from_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error> {
+        self.store
+            .lock()
+            .unwrap()
+            .remove_feed_from_folder(feed_id, folder_id)
+    }
+
+    /// Runs every rule over every stored entry, returning how many actions were
+    /// applied.
+    pub fn apply_rules(&self) -> Result<usize, Error> {
+        let store = self.store.lock().unwrap();
+        let mut entries = vec![];
+        for feed in store.list_feeds()? {
+            entries.extend(store.list_entries(&feed.id, true)?);
+        }
+        rules::apply(&*store, &entries)
+    }
+
+    pub fn list_pending(&self, feed_id: Option<&str>) -> Result<Vec<(String, FeedEntry)>, Error> {
+        self.store.lock().unwrap().list_pending(feed_id)
+    }
+
+    pub fn set_entries_approved(&self, entry_ids: &[String], approved: bool) -> Result<usize, Error> {
+        self.store.lock().unwrap().set_entries_approved(entry_ids, approved)
+    }
+
+    /// Approves or rejects every pending entry, optionally limited to one feed.
+    pub fn set_pending_approved(&self, feed_id: Option<&str>, approved: bool) -> Result<usize, Error> {
+        let store = self.store.lock().unwrap();
+        let entry_ids: Vec<String> = store
+            .list_pending(feed_id)?
+            .into_iter()
+            .map(|(_, entry)| entry.id)
+            .collect();
+        store.set_entries_approved(&entry_ids, approved)
+    }
+
+    /// Pauses or resumes syncing a feed. Resuming also drops any wait the
+    /// server asked for.
+    pub fn set_feed_paused(&self, feed_id: &str, paused: bool) -> Result<(), Error> {
+        let store = self.store.lock().unwrap();
+        store.set_feed_paused(feed_id, paused)?;
+        if !paused {
+            store.set_feed_next_fetch(feed_id, None)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the global retention policy, configured or stored.
+    pub fn get_retention(&self) -> Result<RetentionPolicy, Error> {
+        match &self.retention {
+            Some(policy) => Ok(policy.clone()),
+            None => self.store.lock().unwrap().get_retention(),
+        }
+    }
+
+    /// Stores the global retention policy. Fails when it's configured instead.
+    pub fn set_retention(&self, policy: &RetentionPolicy) -> Result<(), Error> {
+        if self.retention.is_some() {
+            return Err(Error::InvalidArgument(
+                "the global retention policy is set in the config file".into(),
+            ));
+        }
+        self.store
+            .lock()
+            .unwrap()
+            .set_retention(validate_retention(policy)?)
+    }
+
+    pub fn set_feed_retention(&self, feed_id: &str, policy: Option<&RetentionPolicy>) -> Result<(), Error> {
+        let policy = policy.map(validate_retention).transpose()?;
+        self.store.lock().unwrap().set_feed_retention(feed_id, policy)
+    }
+
+    /// Deletes the entries the retention policies don't keep, or with dry_run
+    /// only counts them. With vacuum, the database file is then rebuilt to give
+    /// the freed space back.
+    pub fn prune(&self, dry_run: bool, vacuum: bool) -> Result<PruneReport, Error> {
+        let global = self.get_retention()?;
+        let store = self.store.lock().unwrap();
+        let feeds = store.prune_entries(&global, dry_run)?;
+        if vacuum && !dry_run {
+            store.vacuum()?;
+        }
+        Ok(PruneReport { feeds, dry_run })
+    }
+
+    pub fn set_feed_require_approval(&self, feed_id: &str, require_approval: bool) -> Result<(), Error> {
+        self.store
+            .lock()
+            .unwrap()
+            .set_feed_require_approval(feed_id, require_approval)
+    }
+
+    pub fn set_entry_read(&self, entry_id: &str, read: bool) -> Result<(), Error> {
+        self.store.lock().unwrap().set_entry_read(entry_id, read)
+    }
+
+    pub fn set_entry_starred(&self, entry_id: &str, starred: bool) -> Result<(), Error> {
+        self.store.lock().unwrap().set_entry_starred(entry_id, starred)
+    }
+
+    pub fn list_starred(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
+        self.store.lock().unwrap().list_starred()
+    }
+
+    pub fn search_entries(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchResult>, Error> {
+        if query.trim().is_empty() {
+            return Err(Error::InvalidArgument("search query is empty".into()));
+        }
+        self.store.lock().unwrap().search_entries(query, filters)
+    }
+
+    pub fn set_feed_read(&self, feed_id: &str, read: bool) -> Result<usize, Error> {
+        self.store.lock().unwrap().set_feed_read(feed_id, read)
+    }
+
+    pub fn mark_all_read(&self) -> Result<usize, Error> {
+        self.store.lock().unwrap().mark_all_read()
+    }
+}
+
+/// Stores a fetched feed and runs the rules over the entries that were new,
+/// returning how many there were.
+fn ingest<S: Storage>(
+    store: &S,
+    feed_id: &str,
+    remote: &RemoteFeed,
+    entries: &[RemoteEntry],
+) -> Result<usize, Error> {
+    let new_ids = store.update_feed(feed_id, remote, entries)?;
+    let new_entries = new_ids
+        .iter()
+        .map(|id| store.get_entry(id))
+        .collect::<Result<Vec<_>, _>>()?;
+    rules::apply(store, &new_entries)?;
+    Ok(new_ids.len())
+}
+
+fn validate_folder_name(name: &str) -> Result<&str, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidArgument("folder name is empty".into()));
+    }
+    Ok(name)
+}
+
+fn validate_retention(policy: &RetentionPolicy) -> Result<&RetentionPolicy, Error> {
+    if policy.max_age_days == Some(0) {
+        return Err(Error::InvalidArgument("max age must be at least 1 day".into()));
+    }
+    if policy.max_entries == Some(0) {
+        return Err(Error::InvalidArgument("max entries must be at least 1".into()));
+    }
+    Ok(policy)
+}
+
+/// How long to hold off on a feed after a 429 without a Retry-After, in seconds.
+const DEFAULT_RETRY_AFTER: u64 = 60 * 60;
+
+/// How long to hold off on a feed after its first failure, in seconds.
+const BASE_BACKOFF: u64 = 5 * 60;
+
+/// The longest a feed is held off for, whether backing off or asked to by the
+/// server, in seconds.
+const MAX_WAIT: u64 = 24 * 60 * 60;
+
+/// The longest a sync waits before retrying a fetch.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// How long to hold off on a feed after some number of failures in a row:
+/// BASE_BACKOFF, doubling with each failure after the first, up to MAX_WAIT.
+/// It's jittered so feeds that failed together don't come back together.
+fn backoff(failures: u64) -> u64 {
+    let exponent = failures.saturating_sub(1).min(32) as u32;
+    let wait = BASE_BACKOFF.saturating_mul(2u64.pow(exponent)).min(MAX_WAIT);
+    jitter(Duration::from_secs(wait)).as_secs()
+}
+
+/// Picks a random duration between half of delay and all of it.
+fn jitter(delay: Duration) -> Duration {
+    delay.mul_f64(0.5 + fastrand::f64() / 2.0)
+}
+
+/// Tells failures that may go away on their own right away apart from ones
+/// that won't. Servers that ask to wait are left alone.
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Network(_) | Error::Timeout => true,
+        Error::Http { status, retry_after } => {
+            matches!(status, 500 | 502 | 503 | 504) && retry_after.is_none()
+        }
+        _ => false,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Groups feeds by host for per-host concurrency limits. URLs that don't parse
+/// are each treated as their own host.
+fn host_key(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()))
+        .unwrap_or_else(|| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Records how many fetches are in flight, overall and per host.
+    #[derive(Default)]
+    struct CountingFetcher {
+        in_flight: RefCell<HashMap<String, usize>>,
+        max_total: RefCell<usize>,
+        max_per_host: RefCell<usize>,
+    }
+
+    impl Fetcher for CountingFetcher {
+        async fn fetch(&self, url: &str, _validators: &CacheValidators) -> Result<FetchResult, Error> {
+            let host = host_key(url);
+            {
+                let mut in_flight = self.in_flight.borrow_mut();
+                *in_flight.entry(host.clone()).or_default() += 1;
+                let total: usize = in_flight.values().sum();
+                let mut max_total = self.max_total.borrow_mut();
+                *max_total = (*max_total).max(total);
+                let mut max_per_host = self.max_per_host.borrow_mut();
+                *max_per_host = (*max_per_host).max(in_flight[&host]);
+            }
+
+            // Give the other fetches a chance to start
+            for _ in 0..5 {
+                tokio::task::yield_now().await;
+            }
+
+            *self.in_flight.borrow_mut().get_mut(&host).unwrap() -= 1;
+            if url.ends_with("/broken") {
+                return Err(Error::NotFound);
+            }
+            Ok(FetchResult::NotModified { moved_to: None })
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_all_bounds_concurrency() {
+        let store = sqlite::Store::new_in_memory();
+        for host in ["a.example.com", "b.example.com", "c.example.com"] {
+            for path in ["one", "two", "three", "broken"] {
+                store.add_feed(format!("https://{host}/{path}")).unwrap();
+            }
+        }
+        let core = Core::new(store, CountingFetcher::default()).with_sync_options(SyncOptions {
+            max_concurrent: 4,
+            max_per_host: 2,
+            ..Default::default()
+        });
+
+        let report = core.sync_all().await.unwrap();
+
+        assert_eq!(report.feeds.len(), 12);
+        assert_eq!(report.failed(), 3);
+        assert_eq!(*core.fetcher.max_total.borrow(), 4);
+        assert_eq!(*core.fetcher.max_per_host.borrow(), 2);
+    }
+
+    /// Serves the same two entries for every feed, except for URLs ending in
+    /// /page or /pages, which are web pages linking to one or two feeds. Plain
+    /// HTTP URLs permanently redirect to HTTPS.
+    struct StaticFetcher;
+
+    impl Fetcher for StaticFetcher {
+        async fn fetch(&self, url: &str, _validators: &CacheValidators) -> Result<FetchResult, Error> {
+            let candidate = |path: &str| FeedCandidate {
+                url: format!("https://example.com/{path}"),
+                title: None,
+                content_type: "application/rss+xml".into(),
+            };
+            if url.ends_with("/page") {
+                return Ok(FetchResult::Discovered(vec![candidate("rss")]));
+            }
+            if url.ends_with("/pages") {
+                return Ok(FetchResult::Discovered(vec![candidate("rss"), candidate("atom")]));
+            }
+            if url.ends_with("/gone") {
+                return Err(Error::Http { status: 410, retry_after: None });
+            }
+            if url.ends_with("/busy") {
+                return Err(Error::Http { status: 429, retry_after: Some(600) });
+            }
+            if url.ends_with("/flaky") {
+                return Err(Error::Network("connection reset".into()));
+            }
+
+            let entry = |guid: &str, title: &str| RemoteEntry {
+                title: title.into(),
+                description: String::new(),
+                guid: guid.into(),
+                link: format!("https://example.com/{guid}"),
+                publish_time_unix_secs: None,
+                author: None,
+            };
+            Ok(FetchResult::Fetched(
+                RemoteFeed {
+                    url: url.into(),
+                    title: "Example".into(),
+                    description: String::new(),
+                    skipped_entries: vec![],
+                    validators: CacheValidators::default(),
+                    moved_to: url.strip_prefix("http://").map(|rest| format!("https://{rest}")),
+                },
+                vec![
+                    entry("one", "Sponsored: buy things"),
+                    entry("two", "Security advisory for libfoo"),
+                ],
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn rules_apply_at_ingest() {
+        let core = Core::new(sqlite::Store::new_in_memory(), StaticFetcher);
+        core.add_rule(RuleField::Title, MatchKind::Substring, "sponsored", &RuleAction::Reject)
+            .unwrap();
+        core.add_rule(RuleField::Title, MatchKind::Keywords, "security advisory", &RuleAction::Star)
+            .unwrap();
+
+        let feed = core.add_feed("https://example.com/rss".into()).await.unwrap();
+
+        let entries = core.list_entries(&feed.id, true).unwrap();
+        let sponsored = entries.iter().find(|e| e.guid == "one").unwrap();
+        assert!(sponsored.rejected && !sponsored.approved);
+        let advisory = entries.iter().find(|e| e.guid == "two").unwrap();
+        assert!(advisory.starred && advisory.approved);
+    }
+
+    #[tokio::test]
+    async fn add_feed_follows_discovered_feed() {
+        let core = Core::new(sqlite::Store::new_in_memory(), StaticFetcher);
+
+        let feed = core.add_feed("https://example.com/page".into()).await.unwrap();
+        assert_eq!(feed.url, "https://example.com/rss");
+
+        let Err(Error::MultipleFeeds(candidates)) = core.add_feed("https://example.com/pages".into()).await
+        else {
+            panic!("expected several candidates");
+        };
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(core.list_feeds().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sync_follows_permanent_redirects() {
+        let store = sqlite::Store::new_in_memory();
+        let feed = store.add_feed("http://example.com/rss".into()).unwrap();
+        let core = Core::new(store, StaticFetcher);
+
+        let report = core.sync_all().await.unwrap();
+
+        assert_eq!(report.feeds[0].moved_to.as_deref(), Some("https://example.com/rss"));
+        let feed = core.get_feed(&feed.id).unwrap();
+        assert_eq!(feed.url, "https://example.com/rss");
+        assert_eq!(feed.previous_url.as_deref(), Some("http://example.com/rss"));
+
+        // Adding the old URL subscribes to where it moved
+        let added = core.add_feed("http://example.com/atom".into()).await.unwrap();
+        assert_eq!(added.url, "https://example.com/atom");
+    }
+
+    #[tokio::test]
+    async fn sync_records_failures_of_merged_feeds_against_the_one_left() {
+        let path = std::env::temp_dir().join(format!("seymour-{}.sqlite3", uuid::Uuid::new_v4()));
+        let store = sqlite::Store::open(&path).unwrap();
+        let old = store.add_feed("http://example.com/rss".into()).unwrap();
+        let current = store.add_feed("https://example.com/rss".into()).unwrap();
+        store.set_feed_paused(&current.id, true).unwrap();
+        // Entries can't be stored, so ingesting fails after the merge
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER fail_insert BEFORE INSERT ON feed_entries
+                 BEGIN SELECT RAISE(ABORT, 'insert failed'); END",
+            )
+            .unwrap();
+        let core = Core::new(store, StaticFetcher);
+
+        let report = core.sync_all().await.unwrap();
+
+        let result = &report.feeds[0];
+        assert_eq!(result.feed_id, current.id);
+        assert!(!matches!(result.error, Some(Error::NotFound) | None));
+        assert!(matches!(core.get_feed(&old.id), Err(Error::NotFound)));
+        let merged = core.get_feed(&current.id).unwrap();
+        assert_eq!(merged.consecutive_failures, 1);
+        assert!(merged.last_error.unwrap().contains("insert failed"));
+        drop(core);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sync_pauses_gone_feeds_and_backs_off_busy_ones() {
+        let store = sqlite::Store::new_in_memory();
+        let gone = store.add_feed("https://example.com/gone".into()).unwrap();
+        let busy = store.add_feed("https://example.com/busy".into()).unwrap();
+        store.add_feed("https://example.com/rss".into()).unwrap();
+        let core = Core::new(store, StaticFetcher);
+
+        let report = core.sync_all().await.unwrap();
+        assert_eq!(report.failed(), 2);
+        assert!(report.feeds.iter().any(|f| f.feed_id == gone.id && f.paused));
+        assert!(core.get_feed(&gone.id).unwrap().paused);
+        let retry_at = core.get_feed(&busy.id).unwrap().next_fetch_at.unwrap();
+        assert!(retry_at > unix_now() + 500 && retry_at <= unix_now() + 600);
+
+        // Neither is fetched again until resumed or due
+        let report = core.sync_all().await.unwrap();
+        assert_eq!((report.feeds.len(), report.paused, report.deferred), (1, 1, 1));
+
+        // Resuming fetches it again, and so pauses it again
+        core.set_feed_paused(&gone.id, false).unwrap();
+        let report = core.sync_all().await.unwrap();
+        assert_eq!((report.feeds.len(), report.paused, report.deferred), (2, 0, 1));
+        assert!(core.get_feed(&gone.id).unwrap().paused);
+    }
+
+    #[tokio::test]
+    async fn sync_retries_transient_failures_then_backs_off() {
+        let store = sqlite::Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/flaky".into()).unwrap();
+        let core = Core::new(store, StaticFetcher).with_sync_options(SyncOptions {
+            retry_delay: Duration::ZERO,
+            ..Default::default()
+        });
+
+        let report = core.sync_all().await.unwrap();
+        assert_eq!(report.feeds[0].retries, 2);
+
+        let feed = core.get_feed(&feed.id).unwrap();
+        assert_eq!(feed.consecutive_failures, 1);
+        assert_eq!(feed.last_error.as_deref(), Some("network error: connection reset"));
+        let retry_at = feed.next_fetch_at.unwrap();
+        assert!(retry_at >= unix_now() + BASE_BACKOFF / 2 && retry_at <= unix_now() + BASE_BACKOFF);
+        assert_eq!(report.feeds[0].retry_at, Some(retry_at));
+    }
+
+    #[tokio::test]
+    async fn sync_prunes_after_syncing() {
+        let store = sqlite::Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/rss".into()).unwrap();
+        let core = Core::new(store, StaticFetcher);
+        core.set_retention(&RetentionPolicy {
+            max_entries: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let report = core.sync_all().await.unwrap();
+        assert_eq!((report.feeds[0].new_entries, report.pruned), (2, 1));
+        assert!(report.prune_error.is_none());
+        assert_eq!(core.list_entries(&feed.id, true).unwrap().len(), 1);
+
+        let report = core.sync_all().await.unwrap();
+        assert_eq!((report.feeds[0].new_entries, report.pruned), (0, 0));
+
+        let zero_days = RetentionPolicy {
+            max_age_days: Some(0),
+            ..Default::default()
+        };
+        assert!(matches!(core.set_retention(&zero_days), Err(Error::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn configured_retention_replaces_the_stored_one() {
+        let store = sqlite::Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/rss".into()).unwrap();
+        let mut config = Config::default();
+        config
+            .apply_file("[retention]\nmax_entries = 1", std::path::Path::new("config.toml"))
+            .unwrap();
+        let core = Core::new(store, StaticFetcher).with_config(&config);
+
+        assert_eq!(core.get_retention().unwrap().max_entries, Some(1));
+        assert!(matches!(
+            core.set_retention(&RetentionPolicy::default()),
+            Err(Error::InvalidArgument(_))
+        ));
+        let report = core.sync_all().await.unwrap();
+        assert_eq!(report.pruned, 1);
+        assert_eq!(core.list_entries(&feed.id, true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        for failures in 1..=4 {
+            let full = BASE_BACKOFF << (failures - 1);
+            assert!((full / 2..=full).contains(&backoff(failures)));
+        }
+        assert!(backoff(100) <= MAX_WAIT);
+        assert!(backoff(100) >= MAX_WAIT / 2);
+    }
+
+    #[tokio::test]
+    async fn import_skips_known_feeds() {
+        let core = Core::new(sqlite::Store::new_in_memory(), StaticFetcher);
+        let known = core.add_feed("https://example.com/rss".into()).await.unwrap();
+
+        let report = core
+            .import_opml(
+                r#"<opml version="2.0"><body>
+                  <outline text="Blogs">
+                    <outline text="Known" xmlUrl="https://example.com/rss"/>
+                    <outline text="New" xmlUrl="https://example.com/new"/>
+                    <outline text="New again" xmlUrl="https://example.com/new"/>
+                  </outline>
+                </body></opml>"#,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.feeds.len(), 3);
+        assert_eq!((report.added(), report.skipped(), report.failed()), (1, 2, 0));
+        assert!(report.feeds[1].feed_id.is_some());
+        assert_eq!(report.feeds[1].folder.as_deref(), Some("Blogs"));
+        let feed = core.get_feed(report.feeds[1].feed_id.as_ref().unwrap()).unwrap();
+        assert_eq!(feed.folders, vec!["Blogs"]);
+        assert_eq!(core.list_feeds().unwrap().len(), 2);
+
+        // Skipped feeds still go in their folders
+        assert_eq!(report.feeds[0].feed_id, Some(known.id.clone()));
+        assert_eq!(report.feeds[2].feed_id, report.feeds[1].feed_id);
+        assert_eq!(core.get_feed(&known.id).unwrap().folders, vec!["Blogs"]);
+    }
+
+    #[tokio::test]
+    async fn import_reports_folder_errors_per_feed() {
+        let path = std::env::temp_dir().join(format!("seymour-{}.sqlite3", uuid::Uuid::new_v4()));
+        let store = sqlite::Store::open(&path).unwrap();
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER fail_folder BEFORE INSERT ON folders WHEN NEW.name = 'Broken'
+                 BEGIN SELECT RAISE(ABORT, 'folder failed'); END",
+            )
+            .unwrap();
+        let core = Core::new(store, StaticFetcher);
+
+        let report = core
+            .import_opml(
+                r#"<opml version="2.0"><body>
+                  <outline text="Broken"><outline text="One" xmlUrl="https://example.com/one"/></outline>
+                  <outline text="Tech"><outline text="Two" xmlUrl="https://example.com/two"/></outline>
+                </body></opml>"#,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.added(), 2);
+        assert!(report.feeds[0].feed_id.is_some());
+        assert!(report.feeds[0].folder_error.is_some());
+        assert!(report.feeds[1].folder_error.is_none());
+        let two = core.get_feed(report.feeds[1].feed_id.as_ref().unwrap()).unwrap();
+        assert_eq!(two.folders, vec!["Tech"]);
+        drop(core);
+        std::fs::remove_file(&path).unwrap();
     }
 }
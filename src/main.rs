@@ -1,14 +1,22 @@
 use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
-use chrono::DateTime;
-use clap::{Parser, Subcommand};
-use seycore::{http::FeedFetcher, sqlite::Store, Core, Fetcher, Storage};
+use anyhow::Context;
+use chrono::NaiveDate;
+use clap::{ArgGroup, Parser, Subcommand};
+use seycore::rules::{MatchKind, RuleAction, RuleField};
+use seycore::config::{Config, Setting, Source};
+use seycore::{
+    http::FeedFetcher, sqlite::Store, Core, Error, Fetcher, RetentionPolicy, SearchFilters, Storage, TimelineQuery,
+};
 
-fn with_pager(f: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>) -> anyhow::Result<()> {
-    if io::stdout().is_terminal() {
-        let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
-        if let Ok(mut child) = Command::new(&pager).stdin(Stdio::piped()).spawn() {
+/// Runs f with its output going through the pager, a command line such as
+/// `less -R`, when stdout is a terminal and there is a pager.
+fn with_pager(pager: Option<&str>, f: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>) -> anyhow::Result<()> {
+    let mut command = pager.unwrap_or_default().split_whitespace();
+    if let (true, Some(program)) = (io::stdout().is_terminal(), command.next()) {
+        if let Ok(mut child) = Command::new(program).args(command).stdin(Stdio::piped()).spawn() {
             let mut stdin = child.stdin.take().unwrap();
             let result = f(&mut stdin);
             drop(stdin); // close stdin so pager receives EOF
@@ -19,15 +27,21 @@ fn with_pager(f: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>) -> anyhow::R
     f(&mut io::stdout())
 }
 
-fn format_timestamp(ts: u64) -> String {
-    DateTime::from_timestamp(ts as i64, 0)
-        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-        .unwrap_or_else(|| ts.to_string())
+/// Parses a YYYY-MM-DD date into the unix timestamp of its first second, UTC.
+fn parse_date(s: &str) -> Result<u64, String> {
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| format!("expected YYYY-MM-DD: {e}"))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() as u64)
 }
 
 #[derive(Parser)]
 #[command(name = "seymour")]
 struct Cli {
+    /// Config file to use instead of ~/.seymour/config.toml
+    #[arg(long, global = true, env = "SEYMOUR_CONFIG")]
+    config: Option<PathBuf>,
+    /// Database file to use instead of the configured one
+    #[arg(long, global = true)]
+    db: Option<PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -39,8 +53,14 @@ enum Commands {
         /// Feed ID to describe; omit to list all feeds
         id: Option<String>,
     },
-    /// Add a feed
+    /// Add a feed, given its URL or the URL of a page that links to it
     Add { url: String },
+    /// Remove a feed and all of its entries
+    Remove { id: String },
+    /// Add every feed listed in an OPML file
+    Import { file: PathBuf },
+    /// Print every feed as an OPML document
+    Export,
     /// List entries for a feed
     Entries {
         feed_id: String,
@@ -48,25 +68,352 @@ enum Commands {
         #[arg(long)]
         all: bool,
     },
+    /// Show what an entry looked like before its feed changed it
+    Revisions { entry_id: String },
+    /// Mark an entry, every entry in a feed, or everything as read
+    #[command(group(ArgGroup::new("target").required(true).args(["entry_id", "feed", "all"])))]
+    Read {
+        /// Entry ID to mark read
+        entry_id: Option<String>,
+        /// Mark every entry in this feed read
+        #[arg(long)]
+        feed: Option<String>,
+        /// Mark every entry in every feed read
+        #[arg(long)]
+        all: bool,
+    },
+    /// Mark an entry, or every entry in a feed, as unread
+    #[command(group(ArgGroup::new("target").required(true).args(["entry_id", "feed"])))]
+    Unread {
+        /// Entry ID to mark unread
+        entry_id: Option<String>,
+        /// Mark every entry in this feed unread
+        #[arg(long)]
+        feed: Option<String>,
+    },
+    /// List entries waiting for approval
+    Pending {
+        /// Only list entries from this feed
+        #[arg(long)]
+        feed: Option<String>,
+    },
+    /// Approve entries by ID, or every pending entry
+    #[command(group(ArgGroup::new("target").required(true).args(["entry_ids", "feed", "all"])))]
+    Approve {
+        entry_ids: Vec<String>,
+        /// Approve every pending entry in this feed
+        #[arg(long)]
+        feed: Option<String>,
+        /// Approve every pending entry
+        #[arg(long)]
+        all: bool,
+    },
+    /// Reject entries by ID, or every pending entry
+    #[command(group(ArgGroup::new("target").required(true).args(["entry_ids", "feed", "all"])))]
+    Reject {
+        entry_ids: Vec<String>,
+        /// Reject every pending entry in this feed
+        #[arg(long)]
+        feed: Option<String>,
+        /// Reject every pending entry
+        #[arg(long)]
+        all: bool,
+    },
+    /// Require new entries from a feed to be approved before they show up
+    RequireApproval {
+        feed_id: String,
+        /// Stop requiring approval
+        #[arg(long)]
+        off: bool,
+    },
+    /// Stop syncing a feed until it's resumed
+    Pause { feed_id: String },
+    /// Sync a paused feed again
+    Resume { feed_id: String },
+    /// Manage folders of feeds
+    Folders {
+        #[command(subcommand)]
+        command: FoldersCommand,
+    },
+    /// Set how long entries are kept, globally or for one feed
+    Retention {
+        #[command(subcommand)]
+        command: RetentionCommand,
+    },
+    /// Delete entries the retention policies don't keep; sync-all does this too
+    Prune {
+        /// Only count what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Rebuild the database afterwards to give the freed space back
+        #[arg(long)]
+        vacuum: bool,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Manage rules that act on entries as they're ingested
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// Search entry titles and descriptions
+    Search {
+        /// Words to search for; supports "phrases", OR, NOT and prefix*
+        query: String,
+        /// Only search entries in this feed
+        #[arg(long)]
+        feed: Option<String>,
+        /// Only search entries published on or after this date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        since: Option<u64>,
+        /// Only search entries published before this date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        until: Option<u64>,
+        /// Only search entries not yet marked read
+        #[arg(long)]
+        unread: bool,
+        /// Show at most this many results
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Star an entry
+    Star { entry_id: String },
+    /// Remove the star from an entry
+    Unstar { entry_id: String },
+    /// Show starred entries across all feeds
+    Starred,
     /// Sync all feeds
     SyncAll,
-    /// Show all approved entries across all feeds
-    Timeline,
+    /// Show approved entries across all feeds, newest first
+    Timeline {
+        /// Only show entries not yet marked read
+        #[arg(long)]
+        unread: bool,
+        /// Only show starred entries
+        #[arg(long)]
+        starred: bool,
+        /// Only show entries from this feed; repeat for several feeds
+        #[arg(long)]
+        feed: Vec<String>,
+        /// Only show entries from feeds in this folder, given by ID or name
+        #[arg(long)]
+        folder: Option<String>,
+        /// Only show entries published on or after this date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        since: Option<u64>,
+        /// Only show entries published before this date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        until: Option<u64>,
+        /// Show posts that appear in several feeds only once
+        #[arg(long)]
+        collapse: bool,
+        /// Show at most this many entries; defaults to timeline_limit in the config
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Continue from where a previous page ended
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FoldersCommand {
+    /// List folders
+    List,
+    /// Add a folder
+    Add { name: String },
+    /// Rename a folder, given by ID or name
+    Rename { folder: String, name: String },
+    /// Remove a folder, given by ID or name; its feeds are kept
+    Rm { folder: String },
+    /// Put a feed in a folder, given by ID or name
+    Assign { feed_id: String, folder: String },
+    /// Take a feed out of a folder, given by ID or name
+    Unassign { feed_id: String, folder: String },
+}
+
+#[derive(Subcommand)]
+enum RetentionCommand {
+    /// Show the global policy and the feeds that have their own
+    Show,
+    /// Set the global policy, or a feed's own policy with --feed
+    Set {
+        #[arg(long)]
+        feed: Option<String>,
+        /// Prune entries published more than this many days ago
+        #[arg(long)]
+        max_age_days: Option<u64>,
+        /// Keep only this many of each feed's newest entries
+        #[arg(long)]
+        max_entries: Option<u64>,
+        /// Never prune unread entries
+        #[arg(long)]
+        keep_unread: bool,
+    },
+    /// Keep every entry, or with --feed, have the feed use the global policy
+    Clear {
+        #[arg(long)]
+        feed: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print every setting's effective value and where it came from
+    Show,
+}
+
+#[derive(Subcommand)]
+enum RulesCommand {
+    /// Add a rule; it applies to entries ingested from now on
+    Add {
+        /// Field to match: title, description, link, author or feed
+        #[arg(long)]
+        field: RuleField,
+        /// How to match: substring, regex or keywords
+        #[arg(long = "match", default_value = "substring")]
+        match_kind: MatchKind,
+        /// Text, regex, or comma-separated keywords to match
+        #[arg(long)]
+        pattern: String,
+        /// What to do: approve, reject, read, star or tag:<name>
+        #[arg(long)]
+        action: RuleAction,
+    },
+    /// List rules in the order they're applied
+    List,
+    /// Remove a rule
+    Rm { id: String },
+    /// Run every rule over all existing entries
+    Apply,
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    let core = Core::new(Store::new()?, FeedFetcher {});
+    let mut config = Config::load(cli.config.as_deref())?;
+    if let Some(db) = cli.db {
+        config.db.set(Some(db), Source::Flag("--db"));
+    }
+
+    // The store is only opened by the commands that use it
+    let core = || -> anyhow::Result<_> {
+        let store = match &config.db.value {
+            Some(path) => Store::open(path)?,
+            None => Store::new()?,
+        };
+        Ok(Core::new(store, FeedFetcher::from_config(&config)?).with_config(&config))
+    };
+    let pager = config.pager.value.as_deref();
 
     match cli.command {
-        Commands::Feeds { id: Some(id) } => handle_describe_feed(&core, &id, io::stdout())?,
-        Commands::Feeds { id: None } => handle_list_feeds(&core, io::stdout())?,
-        Commands::Add { url } => handle_add_feed(&core, url, io::stdout()).await?,
-        Commands::Entries { feed_id, all } => handle_list_entries(&core, &feed_id, all, io::stdout())?,
-        Commands::SyncAll => handle_sync_all(&core, io::stdout()).await?,
-        Commands::Timeline => with_pager(|out| handle_timeline(&core, out))?,
+        Commands::Feeds { id: Some(id) } => handle_describe_feed(&core()?, &config, &id, io::stdout())?,
+        Commands::Feeds { id: None } => handle_list_feeds(&core()?, io::stdout())?,
+        Commands::Add { url } => handle_add_feed(&core()?, url, io::stdout()).await?,
+        Commands::Remove { id } => handle_remove_feed(&core()?, &id, io::stdout())?,
+        Commands::Import { file } => {
+            let xml = std::fs::read_to_string(&file)
+                .with_context(|| format!("reading {}", file.display()))?;
+            handle_import(&core()?, &xml, io::stdout()).await?
+        }
+        Commands::Export => handle_export(&core()?, io::stdout())?,
+        Commands::Entries { feed_id, all } => handle_list_entries(&core()?, &config, &feed_id, all, io::stdout())?,
+        Commands::Revisions { entry_id } => handle_revisions(&core()?, &config, &entry_id, io::stdout())?,
+        Commands::Read { entry_id, feed, all: _ } => {
+            handle_set_read(&core()?, entry_id.as_deref(), feed.as_deref(), true, io::stdout())?
+        }
+        Commands::Unread { entry_id, feed } => {
+            handle_set_read(&core()?, entry_id.as_deref(), feed.as_deref(), false, io::stdout())?
+        }
+        Commands::Pending { feed } => {
+            let core = core()?;
+            with_pager(pager, |out| handle_pending(&core, &config, feed.as_deref(), out))?
+        }
+        Commands::Approve { entry_ids, feed, all: _ } => {
+            handle_set_approved(&core()?, &entry_ids, feed.as_deref(), true, io::stdout())?
+        }
+        Commands::Reject { entry_ids, feed, all: _ } => {
+            handle_set_approved(&core()?, &entry_ids, feed.as_deref(), false, io::stdout())?
+        }
+        Commands::RequireApproval { feed_id, off } => {
+            handle_require_approval(&core()?, &feed_id, !off, io::stdout())?
+        }
+        Commands::Pause { feed_id } => handle_set_paused(&core()?, &feed_id, true, io::stdout())?,
+        Commands::Resume { feed_id } => handle_set_paused(&core()?, &feed_id, false, io::stdout())?,
+        Commands::Folders { command } => match command {
+            FoldersCommand::List => handle_list_folders(&core()?, io::stdout())?,
+            FoldersCommand::Add { name } => handle_add_folder(&core()?, &name, io::stdout())?,
+            FoldersCommand::Rename { folder, name } => {
+                handle_rename_folder(&core()?, &folder, &name, io::stdout())?
+            }
+            FoldersCommand::Rm { folder } => handle_remove_folder(&core()?, &folder, io::stdout())?,
+            FoldersCommand::Assign { feed_id, folder } => {
+                handle_assign_folder(&core()?, &feed_id, &folder, true, io::stdout())?
+            }
+            FoldersCommand::Unassign { feed_id, folder } => {
+                handle_assign_folder(&core()?, &feed_id, &folder, false, io::stdout())?
+            }
+        },
+        Commands::Retention { command } => match command {
+            RetentionCommand::Show => handle_show_retention(&core()?, io::stdout())?,
+            RetentionCommand::Set { feed, max_age_days, max_entries, keep_unread } => {
+                let policy = RetentionPolicy { max_age_days, max_entries, keep_unread };
+                handle_set_retention(&core()?, feed.as_deref(), Some(&policy), io::stdout())?
+            }
+            RetentionCommand::Clear { feed } => handle_set_retention(&core()?, feed.as_deref(), None, io::stdout())?,
+        },
+        Commands::Prune { dry_run, vacuum } => handle_prune(&core()?, dry_run, vacuum, io::stdout())?,
+        Commands::Config { command: ConfigCommand::Show } => handle_show_config(&config, io::stdout())?,
+        Commands::Rules { command } => match command {
+            RulesCommand::Add { field, match_kind, pattern, action } => {
+                handle_add_rule(&core()?, field, match_kind, &pattern, &action, io::stdout())?
+            }
+            RulesCommand::List => handle_list_rules(&core()?, io::stdout())?,
+            RulesCommand::Rm { id } => handle_remove_rule(&core()?, &id, io::stdout())?,
+            RulesCommand::Apply => handle_apply_rules(&core()?, io::stdout())?,
+        },
+        Commands::Search { query, feed, since, until, unread, limit } => {
+            let filters = SearchFilters {
+                feed_id: feed,
+                since,
+                until,
+                unread_only: unread,
+                limit,
+            };
+            let core = core()?;
+            with_pager(pager, |out| handle_search(&core, &config, &query, &filters, out))?
+        }
+        Commands::Star { entry_id } => handle_set_starred(&core()?, &entry_id, true, io::stdout())?,
+        Commands::Unstar { entry_id } => handle_set_starred(&core()?, &entry_id, false, io::stdout())?,
+        Commands::Starred => {
+            let core = core()?;
+            with_pager(pager, |out| handle_starred(&core, &config, out))?
+        }
+        Commands::SyncAll => handle_sync_all(&core()?, &config, io::stdout()).await?,
+        Commands::Timeline { unread, starred, feed, folder, since, until, collapse, limit, cursor } => {
+            let core = core()?;
+            let folder_id = match folder {
+                Some(folder) => Some(core.find_folder(&folder)?.id),
+                None => None,
+            };
+            let query = TimelineQuery {
+                cursor,
+                limit: limit.unwrap_or(config.timeline_limit.value),
+                since,
+                until,
+                feed_ids: feed,
+                folder_id,
+                read: unread.then_some(false),
+                starred: starred.then_some(true),
+                collapse_duplicates: collapse,
+                ..Default::default()
+            };
+            with_pager(pager, |out| handle_timeline(&core, &config, &query, out))?
+        }
     }
 
     Ok(())
@@ -77,14 +424,21 @@ async fn main() -> anyhow::Result<()> {
 /// ```text
 ///           ID: 550e8400-e29b-41d4-a716-446655440000
 ///          URL: https://example.com/rss
+/// Previous URL: http://example.com/rss
 ///        Title: My Blog
 ///  Description: A blog about things
 ///  Last Synced: 2026-02-16 12:00:00
+///     Approval: not required
+///    Retention: at most 500 entries, unread kept
+///       Status: active
+///   Last Error: —
+///      Folders: News, Tech
 ///      Created: 2026-02-15 08:30:00
 ///      Updated: 2026-02-16 12:00:00
 /// ```
 fn handle_describe_feed<S: Storage, F: Fetcher>(
     core: &Core<S, F>,
+    config: &Config,
     id: &str,
     mut out: impl Write,
 ) -> anyhow::Result<()> {
@@ -92,6 +446,12 @@ fn handle_describe_feed<S: Storage, F: Fetcher>(
     let none = "—".to_string();
     writeln!(out, "{:>12}: {}", "ID", feed.id)?;
     writeln!(out, "{:>12}: {}", "URL", feed.url)?;
+    writeln!(
+        out,
+        "{:>12}: {}",
+        "Previous URL",
+        feed.previous_url.as_deref().unwrap_or(&none)
+    )?;
     writeln!(
         out,
         "{:>12}: {}",
@@ -104,15 +464,53 @@ fn handle_describe_feed<S: Storage, F: Fetcher>(
         "Description",
         feed.description.as_deref().unwrap_or(&none)
     )?;
-    let last_synced = feed.last_synced_at.map(format_timestamp);
+    let last_synced = feed.last_synced_at.map(|ts| config.format_timestamp(ts));
     writeln!(
         out,
         "{:>12}: {}",
         "Last Synced",
         last_synced.as_deref().unwrap_or(&none)
     )?;
-    writeln!(out, "{:>12}: {}", "Created", format_timestamp(feed.created_at))?;
-    writeln!(out, "{:>12}: {}", "Updated", format_timestamp(feed.updated_at))?;
+    writeln!(
+        out,
+        "{:>12}: {}",
+        "Approval",
+        if feed.require_approval { "required" } else { "not required" }
+    )?;
+    let retention = match &feed.retention {
+        Some(policy) => describe_retention(policy),
+        None => "global".to_string(),
+    };
+    writeln!(out, "{:>12}: {}", "Retention", retention)?;
+    // A backoff that has run out no longer holds the feed back
+    let status = match feed.next_fetch_at {
+        _ if feed.paused => "paused".to_string(),
+        Some(at) if at > chrono::Utc::now().timestamp() as u64 => {
+            format!("waiting until {}", config.format_timestamp(at))
+        }
+        Some(_) => "retrying on next sync".to_string(),
+        None => "active".to_string(),
+    };
+    writeln!(out, "{:>12}: {}", "Status", status)?;
+    let last_error = feed.last_error.as_ref().map(|err| match feed.consecutive_failures {
+        1 => err.clone(),
+        failures => format!("{err} ({failures} failures in a row)"),
+    });
+    writeln!(
+        out,
+        "{:>12}: {}",
+        "Last Error",
+        last_error.as_deref().unwrap_or(&none)
+    )?;
+    let folders = feed.folders.join(", ");
+    writeln!(
+        out,
+        "{:>12}: {}",
+        "Folders",
+        if folders.is_empty() { &none } else { &folders }
+    )?;
+    writeln!(out, "{:>12}: {}", "Created", config.format_timestamp(feed.created_at))?;
+    writeln!(out, "{:>12}: {}", "Updated", config.format_timestamp(feed.updated_at))?;
     Ok(())
 }
 
@@ -121,42 +519,618 @@ async fn handle_add_feed<S: Storage, F: Fetcher>(
     url: String,
     mut out: impl Write,
 ) -> anyhow::Result<()> {
-    let feed = core.add_feed(url).await?;
+    let feed = match core.add_feed(url.clone()).await {
+        Ok(feed) => feed,
+        Err(Error::MultipleFeeds(candidates)) => {
+            writeln!(out, "{url} links to {} feeds; add one of them:", candidates.len())?;
+            let rows: Vec<Vec<String>> = candidates
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.url.clone(),
+                        c.content_type.clone(),
+                        c.title.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            write_table(&["URL", "Type", "Title"], &rows, &mut out)?;
+            anyhow::bail!("no feed added");
+        }
+        Err(err) => return Err(err.into()),
+    };
     writeln!(out, "added feed {} ({})", feed.id, feed.url)?;
     Ok(())
 }
 
+fn handle_remove_feed<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    id: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    core.remove_feed(id)?;
+    writeln!(out, "removed feed {id}")?;
+    Ok(())
+}
+
+async fn handle_import<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    xml: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let report = core.import_opml(xml).await?;
+    let rows: Vec<Vec<String>> = report
+        .feeds
+        .iter()
+        .map(|f| {
+            let (status, error) = match (&f.error, &f.folder_error) {
+                (None, None) => ("added", String::new()),
+                (None, Some(err)) => ("added", format!("folder: {err}")),
+                (Some(Error::AlreadyExists), None) => ("exists", String::new()),
+                (Some(Error::AlreadyExists), Some(err)) => ("exists", format!("folder: {err}")),
+                (Some(err), _) => ("failed", err.to_string()),
+            };
+            vec![
+                f.url.clone(),
+                f.folder.clone().unwrap_or_default(),
+                status.to_string(),
+                error,
+            ]
+        })
+        .collect();
+    write_table(&["URL", "Folder", "Status", "Error"], &rows, &mut out)?;
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} added, {} already subscribed, {} failed",
+        report.added(),
+        report.skipped(),
+        report.failed()
+    )?;
+    Ok(())
+}
+
+fn handle_export<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    write!(out, "{}", core.export_opml()?)?;
+    Ok(())
+}
+
 fn handle_timeline<S: Storage, F: Fetcher>(
     core: &Core<S, F>,
+    config: &Config,
+    query: &TimelineQuery,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let page = core.list_timeline(query)?;
+    let rows: Vec<Vec<String>> = page
+        .entries
+        .iter()
+        .map(|(feed_name, e)| {
+            vec![
+                feed_name.clone(),
+                e.title.clone(),
+                e.publish_time.map(|ts| config.format_timestamp(ts)).unwrap_or_default(),
+                e.link.clone(),
+            ]
+        })
+        .collect();
+    write_table(&["Feed", "Title", "Published", "Link"], &rows, &mut *out)?;
+    if let Some(cursor) = page.next_cursor {
+        writeln!(out)?;
+        writeln!(out, "more entries: pass --cursor {cursor}")?;
+    }
+    Ok(())
+}
+
+fn handle_pending<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    config: &Config,
+    feed_id: Option<&str>,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let entries = core.list_pending(feed_id)?;
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|(feed_name, e)| {
+            vec![
+                e.id.clone(),
+                feed_name.clone(),
+                e.title.clone(),
+                e.publish_time.map(|ts| config.format_timestamp(ts)).unwrap_or_default(),
+                e.link.clone(),
+            ]
+        })
+        .collect();
+    write_table(&["ID", "Feed", "Title", "Published", "Link"], &rows, out)?;
+    Ok(())
+}
+
+/// Approves or rejects the given entries or, with none given, every pending
+/// entry (in one feed, if given).
+fn handle_set_approved<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    entry_ids: &[String],
+    feed_id: Option<&str>,
+    approved: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let changed = if entry_ids.is_empty() {
+        core.set_pending_approved(feed_id, approved)?
+    } else {
+        core.set_entries_approved(entry_ids, approved)?
+    };
+    let action = if approved { "approved" } else { "rejected" };
+    writeln!(out, "{action} {changed} entries")?;
+    Ok(())
+}
+
+fn handle_require_approval<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    feed_id: &str,
+    require_approval: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    core.set_feed_require_approval(feed_id, require_approval)?;
+    if require_approval {
+        writeln!(out, "feed {feed_id} now requires approval")?;
+    } else {
+        writeln!(out, "feed {feed_id} no longer requires approval")?;
+    }
+    Ok(())
+}
+
+fn handle_set_paused<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    feed_id: &str,
+    paused: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    core.set_feed_paused(feed_id, paused)?;
+    let action = if paused { "paused" } else { "resumed" };
+    writeln!(out, "{action} feed {feed_id}")?;
+    Ok(())
+}
+
+/// Describes a retention policy in a few words, e.g. "at most 30 days old,
+/// unread kept".
+fn describe_retention(policy: &RetentionPolicy) -> String {
+    let mut parts = vec![];
+    if let Some(days) = policy.max_age_days {
+        parts.push(format!("at most {days} days old"));
+    }
+    if let Some(entries) = policy.max_entries {
+        parts.push(format!("at most {entries} entries"));
+    }
+    if parts.is_empty() {
+        return "keep everything".to_string();
+    }
+    if policy.keep_unread {
+        parts.push("unread kept".to_string());
+    }
+    parts.join(", ")
+}
+
+fn handle_show_retention<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    writeln!(out, "global: {}", describe_retention(&core.get_retention()?))?;
+    writeln!(out)?;
+    let rows: Vec<Vec<String>> = core
+        .list_feeds()?
+        .iter()
+        .filter_map(|f| {
+            let policy = f.retention.as_ref()?;
+            Some(vec![f.id.clone(), f.url.clone(), describe_retention(policy)])
+        })
+        .collect();
+    write_table(&["ID", "URL", "Retention"], &rows, &mut out)?;
+    Ok(())
+}
+
+/// Sets the global retention policy, or a feed's own one when feed_id is
+/// given. A policy of None keeps everything, or has the feed use the global
+/// policy.
+fn handle_set_retention<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    feed_id: Option<&str>,
+    policy: Option<&RetentionPolicy>,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    match feed_id {
+        Some(feed_id) => {
+            core.set_feed_retention(feed_id, policy)?;
+            match policy {
+                Some(policy) => writeln!(out, "feed {feed_id} retention: {}", describe_retention(policy))?,
+                None => writeln!(out, "feed {feed_id} retention: global")?,
+            }
+        }
+        None => {
+            let policy = policy.cloned().unwrap_or_default();
+            core.set_retention(&policy)?;
+            writeln!(out, "global retention: {}", describe_retention(&policy))?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_prune<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    dry_run: bool,
+    vacuum: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let report = core.prune(dry_run, vacuum)?;
+    let rows: Vec<Vec<String>> = report
+        .feeds
+        .iter()
+        .map(|f| vec![f.url.clone(), f.entries.to_string()])
+        .collect();
+    write_table(&["URL", "Entries"], &rows, &mut out)?;
+
+    writeln!(out)?;
+    if report.dry_run {
+        writeln!(out, "{} entries would be pruned", report.entries())?;
+    } else if vacuum {
+        writeln!(out, "{} entries pruned, database vacuumed", report.entries())?;
+    } else {
+        writeln!(out, "{} entries pruned", report.entries())?;
+    }
+    Ok(())
+}
+
+/// Prints the config file that was read and every setting in a table of
+/// effective values and where they came from.
+fn handle_show_config(config: &Config, mut out: impl Write) -> anyhow::Result<()> {
+    let file = config.file.as_ref().map(|path| path.display().to_string());
+    writeln!(out, "config file: {}", file.as_deref().unwrap_or("none"))?;
+    writeln!(out)?;
+
+    fn row<T>(key: &str, setting: &Setting<T>, value: impl FnOnce(&T) -> String) -> Vec<String> {
+        vec![key.to_string(), value(&setting.value), setting.source.to_string()]
+    }
+    let seconds = |d: &std::time::Duration| format!("{}s", d.as_secs());
+    let rows = vec![
+        row("db", &config.db, |db| match db {
+            Some(path) => path.display().to_string(),
+            None => "~/.seymour/data.sqlite3".to_string(),
+        }),
+        row("user_agent", &config.user_agent, String::clone),
+        row("http.connect_timeout", &config.connect_timeout, seconds),
+        row("http.timeout", &config.timeout, seconds),
+        row("sync.max_concurrent", &config.max_concurrent, usize::to_string),
+        row("sync.max_per_host", &config.max_per_host, usize::to_string),
+        row("sync.max_retries", &config.max_retries, usize::to_string),
+        row("sync.retry_delay", &config.retry_delay, seconds),
+        row("retention", &config.retention, |policy| match policy {
+            Some(policy) => describe_retention(policy),
+            None => "stored, see retention show".to_string(),
+        }),
+        row("timeline_limit", &config.timeline_limit, usize::to_string),
+        row("date_format", &config.date_format, String::clone),
+        row("time_zone", &config.time_zone, |tz| tz.to_string()),
+        row("pager", &config.pager, |pager| pager.clone().unwrap_or_else(|| "off".to_string())),
+    ];
+    write_table(&["Setting", "Value", "Source"], &rows, &mut out)?;
+    Ok(())
+}
+
+fn handle_search<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    config: &Config,
+    query: &str,
+    filters: &SearchFilters,
     out: &mut dyn Write,
 ) -> anyhow::Result<()> {
-    let entries = core.list_timeline()?;
+    let results = core.search_entries(query, filters)?;
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|r| {
+            vec![
+                r.entry.id.clone(),
+                r.feed_name.clone(),
+                r.entry.title.clone(),
+                r.entry.publish_time.map(|ts| config.format_timestamp(ts)).unwrap_or_default(),
+                // Snippets can span lines of the original text
+                r.snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+            ]
+        })
+        .collect();
+    write_table(&["ID", "Feed", "Title", "Published", "Snippet"], &rows, out)?;
+    Ok(())
+}
+
+fn handle_list_folders<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let folders = core.list_folders()?;
+    let rows: Vec<Vec<String>> = folders
+        .iter()
+        .map(|f| vec![f.id.clone(), f.feed_count.to_string(), f.name.clone()])
+        .collect();
+    write_table(&["ID", "Feeds", "Name"], &rows, &mut out)?;
+    Ok(())
+}
+
+fn handle_add_folder<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    name: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let folder = core.add_folder(name)?;
+    writeln!(out, "added folder {} ({})", folder.id, folder.name)?;
+    Ok(())
+}
+
+fn handle_rename_folder<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    folder: &str,
+    name: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let folder = core.find_folder(folder)?;
+    core.rename_folder(&folder.id, name)?;
+    writeln!(out, "renamed folder {} to {}", folder.name, name.trim())?;
+    Ok(())
+}
+
+fn handle_remove_folder<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    folder: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let folder = core.find_folder(folder)?;
+    core.remove_folder(&folder.id)?;
+    writeln!(out, "removed folder {}", folder.name)?;
+    Ok(())
+}
+
+/// Puts a feed in a folder or, when assign is false, takes it out.
+fn handle_assign_folder<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    feed_id: &str,
+    folder: &str,
+    assign: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let folder = core.find_folder(folder)?;
+    if assign {
+        core.add_feed_to_folder(feed_id, &folder.id)?;
+        writeln!(out, "added feed {feed_id} to folder {}", folder.name)?;
+    } else {
+        core.remove_feed_from_folder(feed_id, &folder.id)?;
+        writeln!(out, "removed feed {feed_id} from folder {}", folder.name)?;
+    }
+    Ok(())
+}
+
+fn handle_add_rule<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    field: RuleField,
+    match_kind: MatchKind,
+    pattern: &str,
+    action: &RuleAction,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let rule = core.add_rule(field, match_kind, pattern, action)?;
+    writeln!(out, "added rule {}", rule.id)?;
+    Ok(())
+}
+
+fn handle_list_rules<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let rules = core.list_rules()?;
+    let rows: Vec<Vec<String>> = rules
+        .iter()
+        .map(|r| {
+            vec![
+                r.id.clone(),
+                r.field.to_string(),
+                r.match_kind.to_string(),
+                r.action.to_string(),
+                r.pattern.clone(),
+            ]
+        })
+        .collect();
+    write_table(&["ID", "Field", "Match", "Action", "Pattern"], &rows, &mut out)?;
+    Ok(())
+}
+
+fn handle_remove_rule<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    id: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    core.remove_rule(id)?;
+    writeln!(out, "removed rule {id}")?;
+    Ok(())
+}
+
+fn handle_apply_rules<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let applied = core.apply_rules()?;
+    writeln!(out, "applied {applied} rule actions")?;
+    Ok(())
+}
+
+fn handle_revisions<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    config: &Config,
+    entry_id: &str,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let revisions = core.list_revisions(entry_id)?;
+    let rows: Vec<Vec<String>> = revisions
+        .iter()
+        .map(|r| {
+            vec![
+                config.format_timestamp(r.replaced_at),
+                r.title.clone(),
+                r.publish_time.map(|ts| config.format_timestamp(ts)).unwrap_or_default(),
+                r.link.clone(),
+            ]
+        })
+        .collect();
+    write_table(&["Replaced", "Title", "Published", "Link"], &rows, &mut out)?;
+    Ok(())
+}
+
+fn handle_starred<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    config: &Config,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let entries = core.list_starred()?;
     let rows: Vec<Vec<String>> = entries
         .iter()
         .map(|(feed_name, e)| {
             vec![
+                e.id.clone(),
                 feed_name.clone(),
                 e.title.clone(),
-                e.publish_time.map(format_timestamp).unwrap_or_default(),
+                e.publish_time.map(|ts| config.format_timestamp(ts)).unwrap_or_default(),
                 e.link.clone(),
             ]
         })
         .collect();
-    write_table(&["Feed", "Title", "Published", "Link"], &rows, out)?;
+    write_table(&["ID", "Feed", "Title", "Published", "Link"], &rows, out)?;
+    Ok(())
+}
+
+fn handle_set_starred<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    entry_id: &str,
+    starred: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    core.set_entry_starred(entry_id, starred)?;
+    if starred {
+        writeln!(out, "starred entry {entry_id}")?;
+    } else {
+        writeln!(out, "unstarred entry {entry_id}")?;
+    }
+    Ok(())
+}
+
+/// Sets the read state of a single entry, of every entry in a feed, or (with
+/// neither given) of every entry.
+fn handle_set_read<S: Storage, F: Fetcher>(
+    core: &Core<S, F>,
+    entry_id: Option<&str>,
+    feed_id: Option<&str>,
+    read: bool,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    let state = if read { "read" } else { "unread" };
+    match (entry_id, feed_id) {
+        (Some(entry_id), _) => {
+            core.set_entry_read(entry_id, read)?;
+            writeln!(out, "marked entry {entry_id} {state}")?;
+        }
+        (None, Some(feed_id)) => {
+            let changed = core.set_feed_read(feed_id, read)?;
+            writeln!(out, "marked {changed} entries in feed {feed_id} {state}")?;
+        }
+        (None, None) => {
+            let changed = core.mark_all_read()?;
+            writeln!(out, "marked {changed} entries {state}")?;
+        }
+    }
     Ok(())
 }
 
 async fn handle_sync_all<S: Storage, F: Fetcher>(
     core: &Core<S, F>,
+    config: &Config,
     mut out: impl Write,
 ) -> anyhow::Result<()> {
-    core.sync_all().await?;
-    writeln!(out, "all feeds synced")?;
+    let report = core.sync_all().await?;
+    let rows: Vec<Vec<String>> = report
+        .feeds
+        .iter()
+        .map(|f| {
+            vec![
+                f.url.clone(),
+                if f.error.is_some() { "failed" } else { "ok" }.to_string(),
+                f.new_entries.to_string(),
+                format!("{:.1}s", f.duration.as_secs_f64()),
+                f.error.as_ref().map(|e| e.to_string()).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    write_table(&["URL", "Status", "New", "Duration", "Error"], &rows, &mut out)?;
+
+    let moved: Vec<_> = report.feeds.iter().filter(|f| f.moved_to.is_some()).collect();
+    if !moved.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "feeds that moved permanently:")?;
+        for f in moved {
+            writeln!(out, "  {} -> {}", f.url, f.moved_to.as_deref().unwrap_or_default())?;
+        }
+    }
+
+    let gone: Vec<_> = report.feeds.iter().filter(|f| f.paused).collect();
+    if !gone.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "feeds paused because they're gone:")?;
+        for f in gone {
+            writeln!(out, "  {}", f.url)?;
+        }
+    }
+
+    let backed_off: Vec<_> = report.feeds.iter().filter(|f| f.retry_at.is_some()).collect();
+    if !backed_off.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "feeds backing off:")?;
+        for f in backed_off {
+            let until = config.format_timestamp(f.retry_at.unwrap_or_default());
+            writeln!(out, "  {} until {until}", f.url)?;
+        }
+    }
+
+    for f in report.feeds.iter().filter(|f| !f.skipped_entries.is_empty()) {
+        writeln!(out)?;
+        writeln!(out, "skipped entries in {}:", f.url)?;
+        for reason in &f.skipped_entries {
+            writeln!(out, "  {reason}")?;
+        }
+    }
+
+    writeln!(out)?;
+    write!(
+        out,
+        "{} of {} feeds synced, {} failed",
+        report.feeds.len() - report.failed(),
+        report.feeds.len(),
+        report.failed()
+    )?;
+    if report.paused > 0 {
+        write!(out, ", {} paused", report.paused)?;
+    }
+    if report.deferred > 0 {
+        write!(out, ", {} waiting to retry", report.deferred)?;
+    }
+    if report.pruned > 0 {
+        write!(out, ", {} entries pruned", report.pruned)?;
+    }
+    writeln!(out)?;
+    if let Some(err) = &report.prune_error {
+        writeln!(out, "pruning failed: {err}")?;
+    }
     Ok(())
 }
 
 fn handle_list_entries<S: Storage, F: Fetcher>(
     core: &Core<S, F>,
+    config: &Config,
     feed_id: &str,
     fetch_all: bool,
     mut out: impl Write,
@@ -168,7 +1142,7 @@ fn handle_list_entries<S: Storage, F: Fetcher>(
             vec![
                 e.id.clone(),
                 e.title.clone(),
-                e.publish_time.map(format_timestamp).unwrap_or_default(),
+                e.publish_time.map(|ts| config.format_timestamp(ts)).unwrap_or_default(),
                 e.link.clone(),
             ]
         })
@@ -184,28 +1158,31 @@ fn handle_list_feeds<S: Storage, F: Fetcher>(
     let feeds = core.list_feeds()?;
     let rows: Vec<Vec<String>> = feeds
         .iter()
-        .map(|f| vec![f.id.clone(), f.url.clone()])
+        .map(|f| vec![f.id.clone(), f.url.clone(), f.unread_count.to_string()])
         .collect();
-    write_table(&["ID", "URL"], &rows, &mut out)?;
+    write_table(&["ID", "URL", "Unread"], &rows, &mut out)?;
     Ok(())
 }
 
 const MAX_COL_WIDTH: usize = 36;
 
+/// Shortens s to at most max characters, cutting on a character boundary.
 fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
+    if s.chars().count() <= max {
         s.to_string()
     } else {
-        format!("{}...", &s[..max - 3])
+        let cut = s.char_indices().nth(max - 3).map_or(s.len(), |(i, _)| i);
+        format!("{}...", &s[..cut])
     }
 }
 
 fn write_table(headers: &[&str], rows: &[Vec<String>], mut out: impl Write) -> io::Result<()> {
-    let mut col_widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    let mut col_widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
     for row in rows {
         for (i, cell) in row.iter().enumerate() {
-            if cell.len() > col_widths[i] {
-                col_widths[i] = cell.len();
+            let width = cell.chars().count();
+            if width > col_widths[i] {
+                col_widths[i] = width;
             }
         }
     }
@@ -255,11 +1232,15 @@ fn write_table(headers: &[&str], rows: &[Vec<String>], mut out: impl Write) -> i
 #[cfg(test)]
 mod tests {
     use super::*;
-    use seycore::{Error, Feed, FeedEntry, RemoteEntry, RemoteFeed};
-    use std::path::PathBuf;
+    use seycore::rules::Rule;
+    use seycore::{
+        CacheValidators, EntryRevision, Feed, FeedCandidate, FeedEntry, FeedPruneResult, Folder, FetchResult, RemoteEntry, RemoteFeed,
+        SearchResult, TimelinePage,
+    };
 
     struct MockStore {
         feeds: Vec<Feed>,
+        prune_fails: bool,
     }
 
     impl Default for MockStore {
@@ -274,6 +1255,16 @@ mod tests {
                         last_synced_at: None,
                         created_at: 1767225600, // 2026-01-01 00:00:00 UTC
                         updated_at: 1767225600,
+                        validators: CacheValidators::default(),
+                        require_approval: false,
+                        unread_count: 2,
+                        folders: vec!["News".into(), "Tech".into()],
+                        previous_url: None,
+                        paused: false,
+                        next_fetch_at: None,
+                        consecutive_failures: 0,
+                        last_error: None,
+                        retention: None,
                     },
                     Feed {
                         id: "00000000-0000-0000-0000-000000000002".into(),
@@ -283,8 +1274,23 @@ mod tests {
                         last_synced_at: None,
                         created_at: 1767312000, // 2026-01-02 00:00:00 UTC
                         updated_at: 1767312000,
+                        validators: CacheValidators::default(),
+                        require_approval: true,
+                        unread_count: 0,
+                        folders: vec![],
+                        previous_url: Some("http://example.com/atom".into()),
+                        paused: false,
+                        next_fetch_at: None,
+                        consecutive_failures: 0,
+                        last_error: None,
+                        retention: Some(RetentionPolicy {
+                            max_age_days: None,
+                            max_entries: Some(500),
+                            keep_unread: true,
+                        }),
                     },
                 ],
+                prune_fails: false,
             }
         }
     }
@@ -294,7 +1300,7 @@ mod tests {
             Ok(self.feeds.clone())
         }
 
-        async fn add_feed(&self, url: String) -> Result<Feed, Error> {
+        fn add_feed(&self, url: String) -> Result<Feed, Error> {
             Ok(Feed {
                 url,
                 ..self.feeds.first().unwrap().clone()
@@ -308,15 +1314,246 @@ mod tests {
             }
         }
 
-        fn list_timeline(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
-            Ok(self
+        fn list_timeline(&self, query: &TimelineQuery) -> Result<TimelinePage, Error> {
+            let mut entries: Vec<_> = self
                 .list_entries("00000000-0000-0000-0000-000000000001", false)?
                 .into_iter()
+                .filter(|e| query.read.is_none_or(|read| e.read == read))
+                .filter(|e| query.starred.is_none_or(|starred| e.starred == starred))
                 .map(|e| ("Example Blog".to_string(), e))
+                .collect();
+            let next_cursor = (entries.len() > query.limit).then(|| "1768046400:entry-0001".to_string());
+            entries.truncate(query.limit);
+            Ok(TimelinePage { entries, next_cursor })
+        }
+
+        fn list_pending(&self, feed_id: Option<&str>) -> Result<Vec<(String, FeedEntry)>, Error> {
+            let feed_id = feed_id.unwrap_or("00000000-0000-0000-0000-000000000001");
+            Ok(self
+                .list_entries(feed_id, true)?
+                .into_iter()
+                .take(1)
+                .map(|e| ("Example Blog".to_string(), FeedEntry { approved: false, ..e }))
+                .collect())
+        }
+
+        fn set_entries_approved(&self, entry_ids: &[String], _approved: bool) -> Result<usize, Error> {
+            Ok(entry_ids.len())
+        }
+
+        fn set_feed_require_approval(&self, feed_id: &str, _require_approval: bool) -> Result<(), Error> {
+            self.get_feed(feed_id).map(|_| ())
+        }
+
+        fn set_feed_paused(&self, feed_id: &str, _paused: bool) -> Result<(), Error> {
+            match feed_id {
+                "00000000-0000-0000-0000-000000000001" | "00000000-0000-0000-0000-000000000002" => Ok(()),
+                _ => Err(Error::NotFound),
+            }
+        }
+
+        fn set_feed_next_fetch(&self, feed_id: &str, _next_fetch_at: Option<u64>) -> Result<(), Error> {
+            self.set_feed_paused(feed_id, false)
+        }
+
+        fn record_feed_failure(&self, feed_id: &str, _error: &str, _next_fetch_at: Option<u64>) -> Result<(), Error> {
+            self.set_feed_paused(feed_id, false)
+        }
+
+        fn set_entry_read(&self, entry_id: &str, _read: bool) -> Result<(), Error> {
+            match entry_id {
+                "entry-0001" | "entry-0002" => Ok(()),
+                _ => Err(Error::NotFound),
+            }
+        }
+
+        fn set_entry_starred(&self, entry_id: &str, _starred: bool) -> Result<(), Error> {
+            match entry_id {
+                "entry-0001" | "entry-0002" => Ok(()),
+                _ => Err(Error::NotFound),
+            }
+        }
+
+        fn list_starred(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
+            Ok(self
+                .list_timeline(&TimelineQuery::default())?
+                .entries
+                .into_iter()
+                .filter(|(_, e)| e.starred)
+                .collect())
+        }
+
+        fn set_feed_read(&self, feed_id: &str, _read: bool) -> Result<usize, Error> {
+            Ok(self.list_entries(feed_id, false)?.len())
+        }
+
+        fn mark_all_read(&self) -> Result<usize, Error> {
+            Ok(2)
+        }
+
+        fn get_entry(&self, id: &str) -> Result<FeedEntry, Error> {
+            self.list_entries("00000000-0000-0000-0000-000000000001", true)?
+                .into_iter()
+                .find(|e| e.id == id)
+                .ok_or(Error::NotFound)
+        }
+
+        fn search_entries(&self, query: &str, _filters: &SearchFilters) -> Result<Vec<SearchResult>, Error> {
+            Ok(self
+                .list_timeline(&TimelineQuery::default())?
+                .entries
+                .into_iter()
+                .filter(|(_, e)| e.description.contains(query))
+                .map(|(feed_name, entry)| SearchResult {
+                    feed_name,
+                    snippet: entry.description.replace(query, &format!("[{query}]")),
+                    entry,
+                })
                 .collect())
         }
 
-        fn update_feed(&self, _feed_id: &str, _remote: &RemoteFeed, _entries: &[RemoteEntry]) -> Result<(), Error> {
+        fn update_feed(&self, _feed_id: &str, _remote: &RemoteFeed, _entries: &[RemoteEntry]) -> Result<Vec<String>, Error> {
+            Ok(vec![])
+        }
+
+        fn list_revisions(&self, entry_id: &str) -> Result<Vec<EntryRevision>, Error> {
+            let entry = self.get_entry(entry_id)?;
+            Ok(vec![EntryRevision {
+                id: "revision-0001".into(),
+                entry_id: entry.id,
+                title: format!("{} (draft)", entry.title),
+                description: entry.description,
+                link: entry.link,
+                publish_time: entry.publish_time,
+                replaced_at: 1768132800, // 2026-01-11 12:00:00 UTC
+            }])
+        }
+
+        fn add_rule(&self, field: RuleField, match_kind: MatchKind, pattern: &str, action: &RuleAction) -> Result<Rule, Error> {
+            Ok(Rule {
+                id: "rule-0003".into(),
+                field,
+                match_kind,
+                pattern: pattern.into(),
+                action: action.clone(),
+                created_at: 1768003200,
+            })
+        }
+
+        fn list_rules(&self) -> Result<Vec<Rule>, Error> {
+            Ok(vec![
+                Rule {
+                    id: "rule-0001".into(),
+                    field: RuleField::Title,
+                    match_kind: MatchKind::Substring,
+                    pattern: "Sponsored".into(),
+                    action: RuleAction::Reject,
+                    created_at: 1767225600,
+                },
+                Rule {
+                    id: "rule-0002".into(),
+                    field: RuleField::Title,
+                    match_kind: MatchKind::Keywords,
+                    pattern: "security advisory, CVE".into(),
+                    action: RuleAction::Tag("security".into()),
+                    created_at: 1767312000,
+                },
+            ])
+        }
+
+        fn remove_rule(&self, id: &str) -> Result<(), Error> {
+            match id {
+                "rule-0001" | "rule-0002" => Ok(()),
+                _ => Err(Error::NotFound),
+            }
+        }
+
+        fn tag_entry(&self, entry_id: &str, _tag: &str) -> Result<(), Error> {
+            self.get_entry(entry_id).map(|_| ())
+        }
+
+        fn add_folder(&self, name: &str) -> Result<Folder, Error> {
+            Ok(Folder {
+                id: "folder-0003".into(),
+                name: name.into(),
+                created_at: 1768003200,
+                feed_count: 0,
+            })
+        }
+
+        fn list_folders(&self) -> Result<Vec<Folder>, Error> {
+            Ok(vec![
+                Folder {
+                    id: "folder-0001".into(),
+                    name: "News".into(),
+                    created_at: 1767225600,
+                    feed_count: 1,
+                },
+                Folder {
+                    id: "folder-0002".into(),
+                    name: "Tech".into(),
+                    created_at: 1767225600,
+                    feed_count: 1,
+                },
+            ])
+        }
+
+        fn rename_folder(&self, _id: &str, _name: &str) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn remove_folder(&self, _id: &str) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn add_feed_to_folder(&self, feed_id: &str, _folder_id: &str) -> Result<(), Error> {
+            self.get_feed(feed_id).map(|_| ())
+        }
+
+        fn remove_feed_from_folder(&self, feed_id: &str, _folder_id: &str) -> Result<(), Error> {
+            self.get_feed(feed_id).map(|_| ())
+        }
+
+        fn move_feed(&self, feed_id: &str, _url: &str) -> Result<String, Error> {
+            Ok(feed_id.into())
+        }
+
+        fn remove_feed(&self, id: &str) -> Result<(), Error> {
+            self.get_feed(id).map(|_| ())
+        }
+
+        fn mark_feed_synced(&self, _feed_id: &str) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_retention(&self) -> Result<RetentionPolicy, Error> {
+            Ok(RetentionPolicy {
+                max_age_days: Some(90),
+                max_entries: None,
+                keep_unread: false,
+            })
+        }
+
+        fn set_retention(&self, _policy: &RetentionPolicy) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn set_feed_retention(&self, feed_id: &str, _policy: Option<&RetentionPolicy>) -> Result<(), Error> {
+            self.get_feed(feed_id).map(|_| ())
+        }
+
+        fn prune_entries(&self, _global: &RetentionPolicy, _dry_run: bool) -> Result<Vec<FeedPruneResult>, Error> {
+            if self.prune_fails {
+                return Err(Error::Internal("database is locked".into()));
+            }
+            Ok(vec![FeedPruneResult {
+                feed_id: "00000000-0000-0000-0000-000000000001".into(),
+                url: "https://example.com/rss".into(),
+                entries: 3,
+            }])
+        }
+
+        fn vacuum(&self) -> Result<(), Error> {
             Ok(())
         }
 
@@ -333,6 +1570,13 @@ mod tests {
                         created_at: 1768003200, // 2026-01-10 00:00:00 UTC
                         publish_time: Some(1768046400), // 2026-01-10 12:00:00 UTC
                         approved: true,
+                        read: true,
+                        starred: true,
+                        rejected: false,
+                        author: Some("Jane Doe".into()),
+                        tags: vec![],
+                        duplicate_of: None,
+                        updated_at: None,
                     },
                     FeedEntry {
                         id: "entry-0002".into(),
@@ -344,6 +1588,13 @@ mod tests {
                         created_at: 1768089600, // 2026-01-11 00:00:00 UTC
                         publish_time: Some(1768120200), // 2026-01-11 08:30:00 UTC
                         approved: true,
+                        read: false,
+                        starred: false,
+                        rejected: false,
+                        author: None,
+                        tags: vec![],
+                        duplicate_of: None,
+                        updated_at: None,
                     },
                 ])
             } else {
@@ -363,12 +1614,37 @@ mod tests {
     struct MockFetcher {}
 
     impl Fetcher for MockFetcher {
-        async fn fetch(&self, _url: &str) -> Result<(RemoteFeed, Vec<RemoteEntry>), Error> {
-            Ok((
+        async fn fetch(&self, url: &str, _validators: &CacheValidators) -> Result<FetchResult, Error> {
+            if url == "https://example.com/atom" {
+                return Err(Error::Http { status: 410, retry_after: None });
+            }
+            if url.ends_with("/broken") {
+                return Err(Error::Http { status: 404, retry_after: None });
+            }
+            // Redirects permanently to /feed.xml
+            let moved_to = url.ends_with("/moved").then(|| "https://example.com/feed.xml".to_string());
+            if url == "https://example.com/" {
+                return Ok(FetchResult::Discovered(vec![
+                    FeedCandidate {
+                        url: "https://example.com/rss".into(),
+                        title: Some("Example Blog".into()),
+                        content_type: "application/rss+xml".into(),
+                    },
+                    FeedCandidate {
+                        url: "https://example.com/atom".into(),
+                        title: None,
+                        content_type: "application/atom+xml".into(),
+                    },
+                ]));
+            }
+            Ok(FetchResult::Fetched(
                 RemoteFeed {
                     url: "https://example.com/rss".into(),
                     title: "Example Blog".into(),
                     description: "A blog about things".into(),
+                    skipped_entries: vec![],
+                    validators: CacheValidators::default(),
+                    moved_to,
                 },
                 vec![],
             ))
@@ -389,11 +1665,62 @@ mod tests {
         assert_eq!(output, golden("add_feed.txt"));
     }
 
+    #[tokio::test]
+    async fn add_feed_moved_output() {
+        let mut buf = Vec::new();
+        handle_add_feed(&mock_core(), "https://example.com/moved".into(), &mut buf)
+            .await
+            .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("add_feed_moved.txt"));
+    }
+
+    #[tokio::test]
+    async fn add_feed_candidates_output() {
+        let mut buf = Vec::new();
+        let result = handle_add_feed(&mock_core(), "https://example.com/".into(), &mut buf).await;
+        assert!(result.is_err());
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("add_feed_candidates.txt"));
+    }
+
+    #[tokio::test]
+    async fn import_output() {
+        let mut buf = Vec::new();
+        handle_import(&mock_core(), &golden("import.opml"), &mut buf)
+            .await
+            .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("import.txt"));
+    }
+
+    #[test]
+    fn export_output() {
+        let mut buf = Vec::new();
+        handle_export(&mock_core(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("export.opml"));
+    }
+
+    #[test]
+    fn remove_feed_output() {
+        let mut buf = Vec::new();
+        handle_remove_feed(
+            &mock_core(),
+            "00000000-0000-0000-0000-000000000001",
+            &mut buf,
+        )
+        .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("remove_feed.txt"));
+    }
+
     #[test]
     fn describe_feed_output() {
         let mut buf = Vec::new();
         handle_describe_feed(
             &mock_core(),
+            &Config::default(),
             "00000000-0000-0000-0000-000000000001",
             &mut buf,
         )
@@ -402,11 +1729,30 @@ mod tests {
         assert_eq!(output, golden("describe_feed.txt"));
     }
 
+    #[test]
+    fn describe_feed_expired_backoff_output() {
+        let mut store = MockStore::default();
+        store.feeds[0].next_fetch_at = Some(1767229200); // 2026-01-01 01:00:00 UTC
+        store.feeds[0].consecutive_failures = 3;
+        store.feeds[0].last_error = Some("network error: connection reset".into());
+        let mut buf = Vec::new();
+        handle_describe_feed(
+            &Core::new(store, MockFetcher {}),
+            &Config::default(),
+            "00000000-0000-0000-0000-000000000001",
+            &mut buf,
+        )
+        .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("describe_feed_expired_backoff.txt"));
+    }
+
     #[test]
     fn list_entries_output() {
         let mut buf = Vec::new();
         handle_list_entries(
             &mock_core(),
+            &Config::default(),
             "00000000-0000-0000-0000-000000000001",
             false,
             &mut buf,
@@ -427,16 +1773,261 @@ mod tests {
     #[tokio::test]
     async fn sync_all_output() {
         let mut buf = Vec::new();
-        handle_sync_all(&mock_core(), &mut buf).await.unwrap();
+        handle_sync_all(&mock_core(), &Config::default(), &mut buf).await.unwrap();
         let output = String::from_utf8(buf).unwrap();
         assert_eq!(output, golden("sync_all.txt"));
     }
 
+    #[tokio::test]
+    async fn sync_all_prune_error_output() {
+        let store = MockStore {
+            prune_fails: true,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        handle_sync_all(&Core::new(store, MockFetcher {}), &Config::default(), &mut buf)
+            .await
+            .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("sync_all_prune_error.txt"));
+    }
+
     #[test]
     fn timeline_output() {
         let mut buf = Vec::new();
-        handle_timeline(&mock_core(), &mut buf).unwrap();
+        handle_timeline(&mock_core(), &Config::default(), &TimelineQuery::default(), &mut buf).unwrap();
         let output = String::from_utf8(buf).unwrap();
         assert_eq!(output, golden("timeline.txt"));
     }
+
+    #[test]
+    fn timeline_unread_output() {
+        let mut buf = Vec::new();
+        let query = TimelineQuery {
+            read: Some(false),
+            ..Default::default()
+        };
+        handle_timeline(&mock_core(), &Config::default(), &query, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("timeline_unread.txt"));
+    }
+
+    #[test]
+    fn timeline_page_output() {
+        let mut buf = Vec::new();
+        let query = TimelineQuery {
+            limit: 1,
+            ..Default::default()
+        };
+        handle_timeline(&mock_core(), &Config::default(), &query, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("timeline_page.txt"));
+    }
+
+    #[test]
+    fn pending_output() {
+        let mut buf = Vec::new();
+        handle_pending(&mock_core(), &Config::default(), None, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("pending.txt"));
+    }
+
+    #[test]
+    fn set_approved_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_set_approved(
+            &core,
+            &["entry-0001".to_string(), "entry-0002".to_string()],
+            None,
+            true,
+            &mut buf,
+        )
+        .unwrap();
+        handle_set_approved(&core, &[], None, false, &mut buf).unwrap();
+        handle_require_approval(&core, "00000000-0000-0000-0000-000000000001", true, &mut buf)
+            .unwrap();
+        handle_require_approval(&core, "00000000-0000-0000-0000-000000000001", false, &mut buf)
+            .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("set_approved.txt"));
+    }
+
+    #[test]
+    fn set_paused_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_set_paused(&core, "00000000-0000-0000-0000-000000000002", true, &mut buf).unwrap();
+        handle_set_paused(&core, "00000000-0000-0000-0000-000000000002", false, &mut buf).unwrap();
+        assert!(handle_set_paused(&core, "missing", true, &mut buf).is_err());
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("set_paused.txt"));
+    }
+
+    #[test]
+    fn revisions_output() {
+        let mut buf = Vec::new();
+        handle_revisions(&mock_core(), &Config::default(), "entry-0001", &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("revisions.txt"));
+    }
+
+    #[test]
+    fn retention_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_show_retention(&core, &mut buf).unwrap();
+        let policy = RetentionPolicy {
+            max_age_days: Some(30),
+            max_entries: None,
+            keep_unread: true,
+        };
+        handle_set_retention(&core, None, Some(&policy), &mut buf).unwrap();
+        handle_set_retention(&core, Some("00000000-0000-0000-0000-000000000001"), Some(&policy), &mut buf).unwrap();
+        handle_set_retention(&core, Some("00000000-0000-0000-0000-000000000001"), None, &mut buf).unwrap();
+        handle_set_retention(&core, None, None, &mut buf).unwrap();
+        let zero = RetentionPolicy {
+            max_entries: Some(0),
+            ..Default::default()
+        };
+        assert!(handle_set_retention(&core, None, Some(&zero), &mut buf).is_err());
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("retention.txt"));
+    }
+
+    #[test]
+    fn prune_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_prune(&core, true, false, &mut buf).unwrap();
+        handle_prune(&core, false, true, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("prune.txt"));
+    }
+
+    #[test]
+    fn show_config_output() {
+        let mut config = Config::default();
+        config
+            .apply_file(
+                "timeline_limit = 20\ntime_zone = \"-05:00\"\n\n[retention]\nmax_entries = 200\n",
+                std::path::Path::new("/home/me/.seymour/config.toml"),
+            )
+            .unwrap();
+        config
+            .apply_env(|name| (name == "PAGER").then(|| "more".to_string()))
+            .unwrap();
+        config.db.set(Some("/tmp/test.sqlite3".into()), Source::Flag("--db"));
+
+        let mut buf = Vec::new();
+        handle_show_config(&config, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("show_config.txt"));
+    }
+
+    #[test]
+    fn starred_output() {
+        let mut buf = Vec::new();
+        handle_starred(&mock_core(), &Config::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("starred.txt"));
+    }
+
+    #[test]
+    fn set_starred_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_set_starred(&core, "entry-0002", true, &mut buf).unwrap();
+        handle_set_starred(&core, "entry-0001", false, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("set_starred.txt"));
+    }
+
+    #[test]
+    fn search_output() {
+        let mut buf = Vec::new();
+        handle_search(&mock_core(), &Config::default(), "second", &SearchFilters::default(), &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("search.txt"));
+    }
+
+    #[test]
+    fn table_truncates_non_ascii() {
+        let rows = vec![
+            vec![
+                "entry-0001".to_string(),
+                "Überblick über die wichtigsten Änderungen".to_string(),
+                "https://example.com/1".to_string(),
+            ],
+            vec![
+                "entry-0002".to_string(),
+                "Rust 日本語のタイトルはとても長いのでここで切り詰められるべきですよね本当に".to_string(),
+                "https://example.com/2".to_string(),
+            ],
+        ];
+        let mut buf = Vec::new();
+        write_table(&["ID", "Title", "Link"], &rows, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("table_non_ascii.txt"));
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(parse_date("2026-01-02"), Ok(1767312000));
+        assert!(parse_date("01/02/2026").is_err());
+    }
+
+    #[test]
+    fn folders_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_add_folder(&core, "Rust", &mut buf).unwrap();
+        handle_list_folders(&core, &mut buf).unwrap();
+        handle_rename_folder(&core, "tech", "Technology", &mut buf).unwrap();
+        handle_assign_folder(&core, "00000000-0000-0000-0000-000000000001", "folder-0001", true, &mut buf)
+            .unwrap();
+        handle_assign_folder(&core, "00000000-0000-0000-0000-000000000001", "News", false, &mut buf)
+            .unwrap();
+        handle_remove_folder(&core, "News", &mut buf).unwrap();
+        assert!(handle_remove_folder(&core, "Nonexistent", &mut buf).is_err());
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("folders.txt"));
+    }
+
+    #[test]
+    fn rules_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_add_rule(
+            &core,
+            RuleField::Title,
+            MatchKind::Substring,
+            "Sponsored",
+            &RuleAction::Reject,
+            &mut buf,
+        )
+        .unwrap();
+        handle_list_rules(&core, &mut buf).unwrap();
+        handle_remove_rule(&core, "rule-0001", &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("rules.txt"));
+    }
+
+    #[test]
+    fn set_read_output() {
+        let core = mock_core();
+        let mut buf = Vec::new();
+        handle_set_read(&core, Some("entry-0001"), None, true, &mut buf).unwrap();
+        handle_set_read(
+            &core,
+            None,
+            Some("00000000-0000-0000-0000-000000000001"),
+            false,
+            &mut buf,
+        )
+        .unwrap();
+        handle_set_read(&core, None, None, true, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output, golden("set_read.txt"));
+    }
 }
@@ -0,0 +1,225 @@
+//! Reading and writing subscription lists in OPML 2.0, the format feed readers
+//! use to move subscriptions between each other.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::{Error, Feed};
+
+/// OpmlFeed is a feed subscription read from an OPML document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpmlFeed {
+    pub url: String,
+    pub title: Option<String>,
+    /// The text of the outline the subscription was nested in, if any.
+    pub folder: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Opml {
+    body: Body,
+}
+
+#[derive(Debug, Deserialize)]
+struct Body {
+    #[serde(rename = "outline", default)]
+    outlines: Vec<Outline>,
+}
+
+/// Outline is either a subscription, when it has an xmlUrl, or a folder of
+/// further outlines.
+#[derive(Debug, Deserialize)]
+struct Outline {
+    #[serde(rename = "@text")]
+    text: Option<String>,
+    #[serde(rename = "@title")]
+    title: Option<String>,
+    #[serde(rename = "@xmlUrl", alias = "@xmlurl")]
+    xml_url: Option<String>,
+    #[serde(rename = "outline", default)]
+    outlines: Vec<Outline>,
+}
+
+/// Parses the subscriptions out of an OPML document, in document order.
+/// Subscriptions nested in several folders get the innermost one.
+pub fn parse(xml: &str) -> Result<Vec<OpmlFeed>, Error> {
+    let opml: Opml = serde_xml_rs::SerdeXml::new()
+        .overlapping_sequences(true)
+        .from_str(xml)
+        .map_err(|err| Error::InvalidArgument(format!("invalid OPML: {err}")))?;
+
+    let mut feeds = vec![];
+    collect(&opml.body.outlines, None, &mut feeds);
+    Ok(feeds)
+}
+
+fn collect(outlines: &[Outline], folder: Option<&str>, feeds: &mut Vec<OpmlFeed>) {
+    for outline in outlines {
+        let name = outline
+            .title
+            .as_deref()
+            .or(outline.text.as_deref())
+            .map(str::trim)
+            .filter(|name| !name.is_empty());
+        match outline.xml_url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => feeds.push(OpmlFeed {
+                url: url.to_string(),
+                title: name.map(str::to_string),
+                folder: folder.map(str::to_string),
+            }),
+            _ => collect(&outline.outlines, name.or(folder), feeds),
+        }
+    }
+}
+
+/// Renders feeds as an OPML document. Feeds in folders are nested in an outline
+/// per folder, so a feed in several folders is listed once in each.
+pub fn render(feeds: &[Feed]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"2.0\">\n  \
+         <head>\n    <title>Seymour subscriptions</title>\n  </head>\n  \
+         <body>\n",
+    );
+
+    let mut folders: BTreeMap<&str, Vec<&Feed>> = BTreeMap::new();
+    for feed in feeds {
+        if feed.folders.is_empty() {
+            push_feed(&mut out, feed, "    ");
+        }
+        for folder in &feed.folders {
+            folders.entry(folder).or_default().push(feed);
+        }
+    }
+    for (folder, feeds) in folders {
+        let name = escape(folder);
+        out.push_str(&format!("    <outline text=\"{name}\" title=\"{name}\">\n"));
+        for feed in feeds {
+            push_feed(&mut out, feed, "      ");
+        }
+        out.push_str("    </outline>\n");
+    }
+
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+fn push_feed(out: &mut String, feed: &Feed, indent: &str) {
+    let title = escape(feed.title.as_deref().unwrap_or(&feed.url));
+    out.push_str(&format!(
+        "{indent}<outline type=\"rss\" text=\"{title}\" title=\"{title}\" xmlUrl=\"{}\"/>\n",
+        escape(&feed.url)
+    ));
+}
+
+/// Escapes text for use in an attribute value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CacheValidators;
+
+    #[test]
+    fn parses_nested_outlines() {
+        let feeds = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <opml version="2.0">
+              <head><title>Subscriptions</title></head>
+              <body>
+                <outline type="rss" text="Top Level" xmlUrl="https://example.com/top.xml"/>
+                <outline text="Tech">
+                  <outline type="rss" text="Tech Blog" xmlUrl="https://example.com/tech.xml"/>
+                  <outline text="Rust">
+                    <outline type="rss" title="This Week" text="twir" xmlUrl="https://example.com/twir.xml"/>
+                  </outline>
+                  <outline type="rss" text="" xmlUrl=" https://example.com/untitled.xml "/>
+                </outline>
+                <outline text="Empty folder"/>
+              </body>
+            </opml>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            feeds,
+            vec![
+                OpmlFeed {
+                    url: "https://example.com/top.xml".into(),
+                    title: Some("Top Level".into()),
+                    folder: None,
+                },
+                OpmlFeed {
+                    url: "https://example.com/tech.xml".into(),
+                    title: Some("Tech Blog".into()),
+                    folder: Some("Tech".into()),
+                },
+                OpmlFeed {
+                    url: "https://example.com/twir.xml".into(),
+                    title: Some("This Week".into()),
+                    folder: Some("Rust".into()),
+                },
+                OpmlFeed {
+                    url: "https://example.com/untitled.xml".into(),
+                    title: None,
+                    folder: Some("Tech".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_documents_that_arent_opml() {
+        assert!(matches!(parse("<rss></rss>"), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn rendered_documents_parse_back() {
+        let feed = |url: &str, title: Option<&str>, folders: &[&str]| Feed {
+            id: "id".into(),
+            url: url.into(),
+            title: title.map(str::to_string),
+            description: None,
+            last_synced_at: None,
+            created_at: 0,
+            updated_at: 0,
+            validators: CacheValidators::default(),
+            require_approval: false,
+            unread_count: 0,
+            folders: folders.iter().map(|f| f.to_string()).collect(),
+            previous_url: None,
+            paused: false,
+            next_fetch_at: None,
+            consecutive_failures: 0,
+            last_error: None,
+            retention: None,
+        };
+        let xml = render(&[
+            feed("https://example.com/rss?a=1&b=2", Some("Tom & Jerry's \"Blog\""), &[]),
+            feed("https://example.com/atom", None, &["News", "Tech"]),
+        ]);
+
+        let feeds = parse(&xml).unwrap();
+        assert_eq!(feeds.len(), 3);
+        assert_eq!(feeds[0].url, "https://example.com/rss?a=1&b=2");
+        assert_eq!(feeds[0].title.as_deref(), Some("Tom & Jerry's \"Blog\""));
+        assert_eq!(feeds[0].folder, None);
+        assert_eq!(feeds[1].title.as_deref(), Some("https://example.com/atom"));
+        assert_eq!(feeds[1].folder.as_deref(), Some("News"));
+        assert_eq!(feeds[2].folder.as_deref(), Some("Tech"));
+    }
+}
@@ -0,0 +1,394 @@
+/// This package provides the rule engine that acts on entries as they are
+/// ingested, or retroactively on demand.
+///
+/// A rule pairs a matcher (a field, how to match it, and a pattern) with an
+/// action. Rules are evaluated in the order they were created, so when two
+/// rules disagree (e.g. one approves and one rejects) the newer one wins.
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::{Error, Feed, FeedEntry, Storage};
+
+/// RuleField is the part of an entry a rule looks at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleField {
+    Title,
+    Description,
+    Link,
+    Author,
+    /// The entry's feed, matched against both its title and its URL.
+    Feed,
+}
+
+/// MatchKind is how a rule's pattern is matched against the field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchKind {
+    /// Case-insensitive substring.
+    Substring,
+    /// Regular expression, case-sensitive unless the pattern says otherwise
+    /// (e.g. with `(?i)`).
+    Regex,
+    /// Comma-separated keywords, any of which must appear as whole words,
+    /// case-insensitively.
+    Keywords,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleAction {
+    Approve,
+    Reject,
+    MarkRead,
+    Star,
+    Tag(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub id: String,
+    pub field: RuleField,
+    pub match_kind: MatchKind,
+    pub pattern: String,
+    pub action: RuleAction,
+    pub created_at: u64,
+}
+
+impl fmt::Display for RuleField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RuleField::Title => "title",
+            RuleField::Description => "description",
+            RuleField::Link => "link",
+            RuleField::Author => "author",
+            RuleField::Feed => "feed",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for RuleField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(RuleField::Title),
+            "description" => Ok(RuleField::Description),
+            "link" => Ok(RuleField::Link),
+            "author" => Ok(RuleField::Author),
+            "feed" => Ok(RuleField::Feed),
+            other => Err(Error::InvalidArgument(format!(
+                "unknown rule field {other:?}; expected title, description, link, author or feed"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MatchKind::Substring => "substring",
+            MatchKind::Regex => "regex",
+            MatchKind::Keywords => "keywords",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for MatchKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "substring" => Ok(MatchKind::Substring),
+            "regex" => Ok(MatchKind::Regex),
+            "keywords" => Ok(MatchKind::Keywords),
+            other => Err(Error::InvalidArgument(format!(
+                "unknown match kind {other:?}; expected substring, regex or keywords"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleAction::Approve => write!(f, "approve"),
+            RuleAction::Reject => write!(f, "reject"),
+            RuleAction::MarkRead => write!(f, "read"),
+            RuleAction::Star => write!(f, "star"),
+            RuleAction::Tag(tag) => write!(f, "tag:{tag}"),
+        }
+    }
+}
+
+impl FromStr for RuleAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "approve" => Ok(RuleAction::Approve),
+            "reject" => Ok(RuleAction::Reject),
+            "read" => Ok(RuleAction::MarkRead),
+            "star" => Ok(RuleAction::Star),
+            other => match other.strip_prefix("tag:").map(str::trim) {
+                Some(tag) if !tag.is_empty() => Ok(RuleAction::Tag(tag.to_string())),
+                _ => Err(Error::InvalidArgument(format!(
+                    "unknown rule action {other:?}; expected approve, reject, read, star or tag:<name>"
+                ))),
+            },
+        }
+    }
+}
+
+/// Matcher is a rule's pattern prepared for matching.
+enum Matcher {
+    Substring(String),
+    Regex(Regex),
+    Keywords(Vec<Vec<String>>),
+}
+
+/// CompiledRule is a rule ready to be evaluated against entries.
+pub struct CompiledRule {
+    pub rule: Rule,
+    matcher: Matcher,
+}
+
+/// Checks that a pattern is usable with the given match kind, e.g. that a regex
+/// compiles.
+pub fn validate(match_kind: MatchKind, pattern: &str) -> Result<(), Error> {
+    compile_matcher(match_kind, pattern).map(|_| ())
+}
+
+fn compile_matcher(match_kind: MatchKind, pattern: &str) -> Result<Matcher, Error> {
+    match match_kind {
+        MatchKind::Substring if pattern.is_empty() => {
+            Err(Error::InvalidArgument("substring pattern is empty".to_string()))
+        }
+        MatchKind::Substring => Ok(Matcher::Substring(pattern.to_lowercase())),
+        MatchKind::Regex => Regex::new(pattern)
+            .map(Matcher::Regex)
+            .map_err(|err| Error::InvalidArgument(format!("invalid regex: {err}"))),
+        MatchKind::Keywords => {
+            let keywords: Vec<Vec<String>> = pattern
+                .split(',')
+                .map(words)
+                .filter(|keyword| !keyword.is_empty())
+                .collect();
+            if keywords.is_empty() {
+                return Err(Error::InvalidArgument("keyword list is empty".to_string()));
+            }
+            Ok(Matcher::Keywords(keywords))
+        }
+    }
+}
+
+/// Splits text into lowercase words, dropping punctuation.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+impl Rule {
+    pub fn compile(&self) -> Result<CompiledRule, Error> {
+        Ok(CompiledRule {
+            rule: self.clone(),
+            matcher: compile_matcher(self.match_kind, &self.pattern)?,
+        })
+    }
+}
+
+impl CompiledRule {
+    pub fn matches(&self, entry: &FeedEntry, feed: Option<&Feed>) -> bool {
+        match self.rule.field {
+            RuleField::Title => self.matches_text(&entry.title),
+            RuleField::Description => self.matches_text(&entry.description),
+            RuleField::Link => self.matches_text(&entry.link),
+            RuleField::Author => entry
+                .author
+                .as_deref()
+                .is_some_and(|author| self.matches_text(author)),
+            RuleField::Feed => feed.is_some_and(|feed| {
+                self.matches_text(&feed.url)
+                    || feed.title.as_deref().is_some_and(|t| self.matches_text(t))
+            }),
+        }
+    }
+
+    fn matches_text(&self, text: &str) -> bool {
+        match &self.matcher {
+            Matcher::Substring(needle) => text.to_lowercase().contains(needle),
+            Matcher::Regex(regex) => regex.is_match(text),
+            Matcher::Keywords(keywords) => {
+                let haystack = words(text);
+                keywords.iter().any(|keyword| {
+                    haystack
+                        .windows(keyword.len())
+                        .any(|window| window == keyword.as_slice())
+                })
+            }
+        }
+    }
+}
+
+/// Runs every stored rule against the given entries and applies the actions of
+/// those that match. Returns how many actions were applied.
+pub fn apply<S: Storage>(store: &S, entries: &[FeedEntry]) -> Result<usize, Error> {
+    let rules = store
+        .list_rules()?
+        .iter()
+        .map(Rule::compile)
+        .collect::<Result<Vec<_>, _>>()?;
+    if rules.is_empty() || entries.is_empty() {
+        return Ok(0);
+    }
+
+    let feeds: HashMap<String, Feed> = store
+        .list_feeds()?
+        .into_iter()
+        .map(|feed| (feed.id.clone(), feed))
+        .collect();
+
+    let mut applied = 0;
+    for entry in entries {
+        for rule in &rules {
+            if !rule.matches(entry, feeds.get(&entry.feed_id)) {
+                continue;
+            }
+            let entry_ids = std::slice::from_ref(&entry.id);
+            match &rule.rule.action {
+                RuleAction::Approve => store.set_entries_approved(entry_ids, true).map(|_| ())?,
+                RuleAction::Reject => store.set_entries_approved(entry_ids, false).map(|_| ())?,
+                RuleAction::MarkRead => store.set_entry_read(&entry.id, true)?,
+                RuleAction::Star => store.set_entry_starred(&entry.id, true)?,
+                RuleAction::Tag(tag) => store.tag_entry(&entry.id, tag)?,
+            }
+            applied += 1;
+        }
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(field: RuleField, match_kind: MatchKind, pattern: &str) -> CompiledRule {
+        Rule {
+            id: "rule-1".into(),
+            field,
+            match_kind,
+            pattern: pattern.into(),
+            action: RuleAction::Reject,
+            created_at: 0,
+        }
+        .compile()
+        .unwrap()
+    }
+
+    fn entry(title: &str) -> FeedEntry {
+        FeedEntry {
+            id: "entry-1".into(),
+            feed_id: "feed-1".into(),
+            title: title.into(),
+            description: "".into(),
+            guid: "guid-1".into(),
+            link: "https://example.com/posts/1".into(),
+            created_at: 0,
+            publish_time: None,
+            approved: true,
+            read: false,
+            starred: false,
+            rejected: false,
+            author: Some("Jane Doe".into()),
+            tags: vec![],
+            duplicate_of: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn substring_is_case_insensitive() {
+        let rule = rule(RuleField::Title, MatchKind::Substring, "sponsored");
+        assert!(rule.matches(&entry("[SPONSORED] Buy things"), None));
+        assert!(!rule.matches(&entry("A regular post"), None));
+    }
+
+    #[test]
+    fn regex_matches() {
+        let rule = rule(RuleField::Title, MatchKind::Regex, r"^CVE-\d{4}-\d+");
+        assert!(rule.matches(&entry("CVE-2025-1234: heap overflow"), None));
+        assert!(!rule.matches(&entry("About CVE-2025-1234"), None));
+    }
+
+    #[test]
+    fn keywords_match_whole_words() {
+        let rule = rule(RuleField::Title, MatchKind::Keywords, "security advisory, rce");
+        assert!(rule.matches(&entry("Security Advisory: OpenSSL"), None));
+        assert!(rule.matches(&entry("Unauthenticated RCE in foo"), None));
+        assert!(!rule.matches(&entry("Sourced from elsewhere"), None));
+    }
+
+    #[test]
+    fn matches_author_and_feed() {
+        let author = rule(RuleField::Author, MatchKind::Substring, "jane");
+        assert!(author.matches(&entry("Post"), None));
+
+        let feed = Feed {
+            id: "feed-1".into(),
+            url: "https://blog.example.com/rss".into(),
+            title: Some("Security Blog".into()),
+            description: None,
+            last_synced_at: None,
+            created_at: 0,
+            updated_at: 0,
+            validators: Default::default(),
+            require_approval: false,
+            unread_count: 0,
+            folders: vec![],
+            previous_url: None,
+            paused: false,
+            next_fetch_at: None,
+            consecutive_failures: 0,
+            last_error: None,
+            retention: None,
+        };
+        let by_title = rule(RuleField::Feed, MatchKind::Substring, "security");
+        let by_url = rule(RuleField::Feed, MatchKind::Substring, "blog.example.com");
+        assert!(by_title.matches(&entry("Post"), Some(&feed)));
+        assert!(by_url.matches(&entry("Post"), Some(&feed)));
+        assert!(!by_title.matches(&entry("Post"), None));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(matches!(
+            validate(MatchKind::Regex, "("),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            validate(MatchKind::Keywords, " , "),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            validate(MatchKind::Substring, ""),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn parses_actions() {
+        assert_eq!("star".parse::<RuleAction>().unwrap(), RuleAction::Star);
+        assert_eq!(
+            "tag:security".parse::<RuleAction>().unwrap(),
+            RuleAction::Tag("security".into())
+        );
+        assert!("tag:".parse::<RuleAction>().is_err());
+        assert!("delete".parse::<RuleAction>().is_err());
+        assert_eq!(RuleAction::Tag("security".into()).to_string(), "tag:security");
+    }
+}
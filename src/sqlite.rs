@@ -1,13 +1,20 @@
 /// This package provides the sqlite implementation of the seymour store.
 ///
-/// It can be configured to point at a different database file, but most often
-/// points at $HOME/.seymour/data.sqlite3.
+/// It can be opened on any database file, but most often points at
+/// $HOME/.seymour/data.sqlite3.
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 
-use crate::{Error, Feed, FeedEntry, RemoteEntry, RemoteFeed, Storage};
+use crate::dedup;
+use crate::rules::{MatchKind, Rule, RuleAction, RuleField};
+use crate::{
+    CacheValidators, EntryRevision, Error, Feed, FeedEntry, FeedPruneResult, Folder, RemoteEntry, RemoteFeed, RetentionPolicy, SearchFilters,
+    SearchResult, Storage, TimelinePage, TimelineQuery,
+};
 
 /// Store implementes all of the methods against a sqlite3 connection.
 ///
@@ -16,38 +23,66 @@ pub struct Store {
     conn: Connection,
 }
 
+/// How long Store::open waits for another connection to release a lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 impl Store {
     // Creates an instace of the storage that is backed by .seymour/data.sqlite3.
     pub fn new() -> Result<Self, Error> {
-        let dir = dirs::home_dir()
-            .ok_or_else(|| Error::Internal("could not determine home directory".into()))?
-            .join(".seymour");
-
-        fs::create_dir_all(&dir)?;
+        Self::open(Self::default_path()?)
+    }
 
-        let path: PathBuf = dir.join("data.sqlite3");
-        let mut conn = Connection::open(&path)?;
+    /// Opens the database at path, creating the file and the directories it's
+    /// in if they don't exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
 
-        // Run migrations on connection
-        MIGRATIONS
-            .to_latest(&mut conn)
-            .map_err(|err| Error::Internal(err.to_string()))?;
+        let conn = Connection::open(path)
+            .map_err(|err| Error::Internal(format!("opening {}: {err}", path.display())))?;
+        // The file may be shared with another process, like a widget. Writers
+        // wait for each other instead of failing with SQLITE_BUSY, and with WAL
+        // readers don't block the writer.
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        Self::from_connection(conn)
+    }
 
-        Ok(Self { conn })
+    /// Where Store::new keeps the database: $HOME/.seymour/data.sqlite3.
+    pub fn default_path() -> Result<PathBuf, Error> {
+        let home = dirs::home_dir()
+            .ok_or_else(|| Error::Internal("could not determine home directory".into()))?;
+        Ok(home.join(".seymour").join("data.sqlite3"))
     }
 
     pub fn new_in_memory() -> Self {
-        let mut conn =
+        let conn =
             Connection::open_in_memory().expect("error opening in-memory sqlite connection");
-        MIGRATIONS
+        Self::from_connection(conn).expect("failed to run migrations")
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, Error> {
+        // Migrations that rebuild a table run with foreign keys off, or dropping
+        // the old table would cascade to the rows referencing it.
+        conn.pragma_update(None, "foreign_keys", false)?;
+
+        // Run migrations on connection
+        migrations()
             .to_latest(&mut conn)
-            .expect("failed to run migrations");
-        Self { conn }
+            .map_err(|err| Error::Internal(err.to_string()))?;
+
+        // Foreign keys are off by default in sqlite and have to be turned on for
+        // every connection, outside of any transaction.
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        Ok(Self { conn })
     }
 }
 
 impl Storage for Store {
-    async fn add_feed(&self, url: String) -> Result<Feed, Error> {
+    fn add_feed(&self, url: String) -> Result<Feed, Error> {
         let id = uuid::Uuid::new_v4().to_string();
         self.conn
             .execute("INSERT INTO feeds (id, url) VALUES (?1, ?2)", [&id, &url])
@@ -60,39 +95,21 @@ impl Storage for Store {
                 other => other.into(),
             })?;
 
-        self.conn.query_row(
-            "SELECT id, url, title, description, last_synced_at, created_at, updated_at FROM feeds WHERE id = ?1",
-            [&id],
-            |row| {
-                Ok(Feed {
-                    id: row.get(0)?,
-                    url: row.get(1)?,
-                    title: row.get(2)?,
-                    description: row.get(3)?,
-                    last_synced_at: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
-                    created_at: row.get::<_, i64>(5)? as u64,
-                    updated_at: row.get::<_, i64>(6)? as u64,
-                })
-            },
-        ).map_err(|err| err.into())
+        self.conn
+            .query_row(
+                &format!("SELECT {FEED_COLUMNS} FROM feeds WHERE id = ?1"),
+                [&id],
+                feed_from_row,
+            )
+            .map_err(|err| err.into())
     }
 
     fn get_feed(&self, id: &str) -> Result<Feed, Error> {
         self.conn
             .query_row(
-                "SELECT id, url, title, description, last_synced_at, created_at, updated_at FROM feeds WHERE id = ?1",
+                &format!("SELECT {FEED_COLUMNS} FROM feeds WHERE id = ?1"),
                 [id],
-                |row| {
-                    Ok(Feed {
-                        id: row.get(0)?,
-                        url: row.get(1)?,
-                        title: row.get(2)?,
-                        description: row.get(3)?,
-                        last_synced_at: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
-                        created_at: row.get::<_, i64>(5)? as u64,
-                        updated_at: row.get::<_, i64>(6)? as u64,
-                    })
-                },
+                feed_from_row,
             )
             .map_err(|e| match e {
                 rusqlite::Error::QueryReturnedNoRows => Error::NotFound,
@@ -102,179 +119,2118 @@ impl Storage for Store {
 
     fn list_entries(&self, feed_id: &str, fetch_all: bool) -> Result<Vec<FeedEntry>, Error> {
         let sql = if fetch_all {
-            "SELECT id, feed_id, title, description, guid, link, created_at, publish_time, approved FROM feed_entries WHERE feed_id = ?1 ORDER BY publish_time DESC, created_at DESC"
+            format!("SELECT {ENTRY_COLUMNS} FROM feed_entries fe WHERE fe.feed_id = ?1 ORDER BY fe.publish_time DESC, fe.created_at DESC")
         } else {
-            "SELECT id, feed_id, title, description, guid, link, created_at, publish_time, approved FROM feed_entries WHERE feed_id = ?1 AND approved = 1 ORDER BY publish_time DESC, created_at DESC"
+            format!("SELECT {ENTRY_COLUMNS} FROM feed_entries fe WHERE fe.feed_id = ?1 AND fe.approved = 1 ORDER BY fe.publish_time DESC, fe.created_at DESC")
         };
-        let mut stmt = self.conn.prepare(sql)?;
-        let entry_iter = stmt.query_map([feed_id], |row| {
-            Ok(FeedEntry {
-                id: row.get(0)?,
-                feed_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                guid: row.get(4)?,
-                link: row.get(5)?,
-                created_at: row.get::<_, i64>(6)? as u64,
-                publish_time: row.get::<_, Option<i64>>(7)?.map(|v| v as u64),
-                approved: row.get::<_, i64>(8)? != 0,
-            })
-        })?;
+        let mut stmt = self.conn.prepare(&sql)?;
+        let entry_iter = stmt.query_map([feed_id], entry_from_row)?;
 
         Ok(entry_iter.map(|e| e.unwrap()).collect())
     }
 
-    fn list_timeline(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
-        let mut stmt = self.conn.prepare(
-            "SELECT fe.id, fe.feed_id, fe.title, fe.description, fe.guid, fe.link, fe.created_at, fe.publish_time, fe.approved, COALESCE(f.title, f.url) as feed_name
+    fn list_starred(&self) -> Result<Vec<(String, FeedEntry)>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS}, COALESCE(f.title, f.url) as feed_name
              FROM feed_entries fe
              JOIN feeds f ON f.id = fe.feed_id
-             WHERE fe.approved = 1
+             WHERE fe.starred = 1
              ORDER BY fe.publish_time DESC, fe.created_at DESC"
-        )?;
+        ))?;
         let entry_iter = stmt.query_map([], |row| {
-            let entry = FeedEntry {
-                id: row.get(0)?,
-                feed_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                guid: row.get(4)?,
-                link: row.get(5)?,
-                created_at: row.get::<_, i64>(6)? as u64,
-                publish_time: row.get::<_, Option<i64>>(7)?.map(|v| v as u64),
-                approved: row.get::<_, i64>(8)? != 0,
-            };
-            let feed_name: String = row.get(9)?;
+            let entry = entry_from_row(row)?;
+            let feed_name: String = row.get(ENTRY_COLUMN_COUNT)?;
             Ok((feed_name, entry))
         })?;
 
         Ok(entry_iter.map(|e| e.unwrap()).collect())
     }
 
-    fn update_feed(&self, feed_id: &str, remote: &RemoteFeed, entries: &[RemoteEntry]) -> Result<(), Error> {
-        self.conn.execute(
-            "UPDATE feeds SET title = ?1, description = ?2, last_synced_at = unixepoch() WHERE id = ?3",
-            rusqlite::params![remote.title, remote.description, feed_id],
+    fn list_timeline(&self, query: &TimelineQuery) -> Result<TimelinePage, Error> {
+        let after = query.cursor.as_deref().map(parse_cursor).transpose()?;
+        let feed_ids = (!query.feed_ids.is_empty())
+            .then(|| serde_json::to_string(&query.feed_ids).unwrap());
+
+        // Entries are ordered by a key that's unique per entry, so a page can
+        // pick up right after the last entry of the previous one. One entry more
+        // than the limit is fetched to tell whether there's another page.
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS}, COALESCE(f.title, f.url) as feed_name,
+                 COALESCE(fe.publish_time, fe.created_at) as sort_time
+             FROM feed_entries fe
+             JOIN feeds f ON f.id = fe.feed_id
+             WHERE (?1 IS NULL OR sort_time < ?1 OR (sort_time = ?1 AND fe.id < ?2))
+               AND (?3 IS NULL OR sort_time >= ?3)
+               AND (?4 IS NULL OR sort_time < ?4)
+               AND (?5 IS NULL OR fe.feed_id IN (SELECT value FROM json_each(?5)))
+               AND (?10 IS NULL OR fe.feed_id IN (SELECT feed_id FROM feed_folders WHERE folder_id = ?10))
+               AND (?6 IS NULL OR fe.read = ?6)
+               AND (?7 IS NULL OR fe.starred = ?7)
+               AND (?8 IS NULL OR fe.approved = ?8)
+               AND NOT (?11 AND EXISTS (
+                 SELECT 1 FROM feed_entries o WHERE o.id = fe.duplicate_of
+                   AND (?5 IS NULL OR o.feed_id IN (SELECT value FROM json_each(?5)))
+                   AND (?10 IS NULL OR o.feed_id IN (SELECT feed_id FROM feed_folders WHERE folder_id = ?10))
+                   AND (?8 IS NULL OR o.approved = ?8)
+               ))
+             ORDER BY sort_time DESC, fe.id DESC
+             LIMIT ?9"
+        ))?;
+        let mut entries = stmt
+            .query_map(
+                rusqlite::params![
+                    after.as_ref().map(|(time, _)| *time),
+                    after.as_ref().map(|(_, id)| id),
+                    query.since.map(|t| t as i64),
+                    query.until.map(|t| t as i64),
+                    feed_ids,
+                    query.read,
+                    query.starred,
+                    query.approved,
+                    query.limit as i64 + 1,
+                    query.folder_id,
+                    query.collapse_duplicates,
+                ],
+                |row| {
+                    let entry = entry_from_row(row)?;
+                    let feed_name: String = row.get(ENTRY_COLUMN_COUNT)?;
+                    let sort_time: i64 = row.get(ENTRY_COLUMN_COUNT + 1)?;
+                    Ok((feed_name, entry, sort_time))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next_cursor = if entries.len() > query.limit {
+            entries.truncate(query.limit);
+            entries.last().map(|(_, e, time)| format!("{time}:{}", e.id))
+        } else {
+            None
+        };
+
+        Ok(TimelinePage {
+            entries: entries.into_iter().map(|(name, e, _)| (name, e)).collect(),
+            next_cursor,
+        })
+    }
+
+    fn list_pending(&self, feed_id: Option<&str>) -> Result<Vec<(String, FeedEntry)>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS}, COALESCE(f.title, f.url) as feed_name
+             FROM feed_entries fe
+             JOIN feeds f ON f.id = fe.feed_id
+             WHERE fe.approved = 0 AND fe.rejected = 0 AND (?1 IS NULL OR fe.feed_id = ?1)
+             ORDER BY fe.publish_time DESC, fe.created_at DESC"
+        ))?;
+        let entry_iter = stmt.query_map([feed_id], |row| {
+            let entry = entry_from_row(row)?;
+            let feed_name: String = row.get(ENTRY_COLUMN_COUNT)?;
+            Ok((feed_name, entry))
+        })?;
+
+        Ok(entry_iter.map(|e| e.unwrap()).collect())
+    }
+
+    fn set_entries_approved(&self, entry_ids: &[String], approved: bool) -> Result<usize, Error> {
+        // All or nothing: an unknown ID rolls back the whole batch
+        let tx = self.conn.unchecked_transaction()?;
+        let mut seen = HashSet::new();
+        for entry_id in entry_ids {
+            if !seen.insert(entry_id) {
+                continue;
+            }
+            let updated = tx.execute(
+                "UPDATE feed_entries SET approved = ?1, rejected = ?2 WHERE id = ?3",
+                rusqlite::params![approved, !approved, entry_id],
+            )?;
+            if updated == 0 {
+                return Err(Error::NotFound);
+            }
+        }
+        tx.commit()?;
+        Ok(seen.len())
+    }
+
+    fn set_feed_require_approval(&self, feed_id: &str, require_approval: bool) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feeds SET require_approval = ?1 WHERE id = ?2",
+            rusqlite::params![require_approval, feed_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn set_feed_paused(&self, feed_id: &str, paused: bool) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feeds SET paused = ?1 WHERE id = ?2",
+            rusqlite::params![paused, feed_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn set_feed_next_fetch(&self, feed_id: &str, next_fetch_at: Option<u64>) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feeds SET next_fetch_at = ?1 WHERE id = ?2",
+            rusqlite::params![next_fetch_at.map(|at| at as i64), feed_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn record_feed_failure(&self, feed_id: &str, error: &str, next_fetch_at: Option<u64>) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feeds SET consecutive_failures = consecutive_failures + 1, last_error = ?1, next_fetch_at = ?2
+            WHERE id = ?3",
+            rusqlite::params![error, next_fetch_at.map(|at| at as i64), feed_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn set_entry_read(&self, entry_id: &str, read: bool) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feed_entries SET read = ?1 WHERE id = ?2",
+            rusqlite::params![read, entry_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn set_entry_starred(&self, entry_id: &str, starred: bool) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feed_entries SET starred = ?1 WHERE id = ?2",
+            rusqlite::params![starred, entry_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn set_feed_read(&self, feed_id: &str, read: bool) -> Result<usize, Error> {
+        // Distinguish a feed with nothing to update from one that doesn't exist
+        self.get_feed(feed_id)?;
+        let updated = self.conn.execute(
+            "UPDATE feed_entries SET read = ?1 WHERE feed_id = ?2 AND read != ?1",
+            rusqlite::params![read, feed_id],
+        )?;
+        Ok(updated)
+    }
+
+    fn mark_all_read(&self) -> Result<usize, Error> {
+        let updated = self
+            .conn
+            .execute("UPDATE feed_entries SET read = 1 WHERE read = 0", [])?;
+        Ok(updated)
+    }
+
+    fn get_entry(&self, id: &str) -> Result<FeedEntry, Error> {
+        self.conn
+            .query_row(
+                &format!("SELECT {ENTRY_COLUMNS} FROM feed_entries fe WHERE fe.id = ?1"),
+                [id],
+                entry_from_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Error::NotFound,
+                other => other.into(),
+            })
+    }
+
+    fn search_entries(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchResult>, Error> {
+        // Matches in the title count for more than matches in the description
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {ENTRY_COLUMNS}, COALESCE(f.title, f.url) as feed_name,
+                 snippet(entries_fts, -1, '[', ']', '...', 12)
+             FROM entries_fts
+             JOIN feed_entries fe ON fe.rowid = entries_fts.rowid
+             JOIN feeds f ON f.id = fe.feed_id
+             WHERE entries_fts MATCH ?1 AND fe.approved = 1
+               AND (?2 IS NULL OR fe.feed_id = ?2)
+               AND (?3 IS NULL OR COALESCE(fe.publish_time, fe.created_at) >= ?3)
+               AND (?4 IS NULL OR COALESCE(fe.publish_time, fe.created_at) < ?4)
+               AND (?5 = 0 OR fe.read = 0)
+             ORDER BY bm25(entries_fts, 10.0, 1.0)
+             LIMIT ?6"
+        ))?;
+        let results = stmt
+            .query_map(
+                rusqlite::params![
+                    query,
+                    filters.feed_id,
+                    filters.since.map(|t| t as i64),
+                    filters.until.map(|t| t as i64),
+                    filters.unread_only,
+                    filters.limit as i64,
+                ],
+                |row| {
+                    Ok(SearchResult {
+                        entry: entry_from_row(row)?,
+                        feed_name: row.get(ENTRY_COLUMN_COUNT)?,
+                        snippet: row.get(ENTRY_COLUMN_COUNT + 1)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>();
+
+        // A query FTS5 can't parse only fails once the statement is stepped, and
+        // does so with a plain SQLITE_ERROR
+        results.map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(err, msg) if err.extended_code == rusqlite::ffi::SQLITE_ERROR => {
+                Error::InvalidArgument(format!("bad search query: {}", msg.unwrap_or_default()))
+            }
+            other => other.into(),
+        })
+    }
+
+    fn update_feed(&self, feed_id: &str, remote: &RemoteFeed, entries: &[RemoteEntry]) -> Result<Vec<String>, Error> {
+        // All or nothing: the new validators are only saved along with the
+        // entries, or the next sync would get a 304 and never see them
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE feeds SET title = ?1, description = ?2, etag = ?3, last_modified = ?4, last_synced_at = unixepoch(),
+                last_fetched_at = unixepoch(), next_fetch_at = NULL, consecutive_failures = 0, last_error = NULL WHERE id = ?5",
+            rusqlite::params![
+                remote.title,
+                remote.description,
+                remote.validators.etag,
+                remote.validators.last_modified,
+                feed_id
+            ],
         )?;
 
+        // New entries from feeds that require approval wait in the pending queue
+        let require_approval: bool = tx
+            .query_row(
+                "SELECT require_approval FROM feeds WHERE id = ?1",
+                [feed_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Error::NotFound,
+                other => other.into(),
+            })?;
+
+        let mut inserted = vec![];
+        // A GUID listed twice is only taken once, or its entry would flip
+        // between the two versions on every sync
+        let mut seen = HashSet::new();
         for entry in entries {
+            if !seen.insert(entry.guid.as_str()) {
+                continue;
+            }
             let id = uuid::Uuid::new_v4().to_string();
-            self.conn.execute(
-                "INSERT OR IGNORE INTO feed_entries (id, feed_id, title, description, guid, link, publish_time, approved) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)",
-                rusqlite::params![id, feed_id, entry.title, entry.description, entry.guid, entry.link, entry.publish_time_unix_secs.map(|s| s as i64)],
+            // Entries pruned before stay pruned
+            let changed = tx.execute(
+                "INSERT OR IGNORE INTO feed_entries (id, feed_id, title, description, guid, link, publish_time, approved, author)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
+                 WHERE NOT EXISTS (SELECT 1 FROM pruned_entries WHERE feed_id = ?2 AND guid = ?5)",
+                rusqlite::params![id, feed_id, entry.title, entry.description, entry.guid, entry.link, entry.publish_time_unix_secs.map(|s| s as i64), !require_approval, entry.author],
             )?;
+            if changed > 0 {
+                set_dedup_keys(&tx, &id, &entry.link, &entry.title)?;
+                inserted.push(id);
+            } else {
+                revise_entry(&tx, feed_id, entry)?;
+                // A pruned entry's tombstone lives as long as the feed lists it
+                tx.execute(
+                    "UPDATE pruned_entries SET last_seen_at = (SELECT last_fetched_at FROM feeds WHERE id = ?1)
+                     WHERE feed_id = ?1 AND guid = ?2",
+                    [feed_id, &entry.guid],
+                )?;
+            }
         }
 
-        Ok(())
+        tx.commit()?;
+        Ok(inserted)
     }
 
-    /// Lists all feeds tracked within the store.
-    fn list_feeds(&self) -> Result<Vec<Feed>, Error> {
+    fn list_revisions(&self, entry_id: &str) -> Result<Vec<EntryRevision>, Error> {
+        self.get_entry(entry_id)?;
         let mut stmt = self.conn.prepare(
-            "SELECT id, url, title, description, last_synced_at, created_at, updated_at FROM feeds;"
+            "SELECT id, entry_id, title, description, link, publish_time, replaced_at FROM entry_revisions
+             WHERE entry_id = ?1
+             ORDER BY replaced_at DESC, rowid DESC",
         )?;
-        let fd_iter = stmt.query_map([], |row| {
-            Ok(Feed {
-                id: row.get(0)?,
-                url: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                last_synced_at: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
-                created_at: row.get::<_, i64>(5)? as u64,
-                updated_at: row.get::<_, i64>(6)? as u64,
-            })
-        })?;
+        let revisions = stmt
+            .query_map([entry_id], |row| {
+                Ok(EntryRevision {
+                    id: row.get(0)?,
+                    entry_id: row.get(1)?,
+                    title: row.get(2)?,
+                    description: row.get(3)?,
+                    link: row.get(4)?,
+                    publish_time: row.get::<_, Option<i64>>(5)?.map(|v| v as u64),
+                    replaced_at: row.get::<_, i64>(6)? as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(revisions)
+    }
 
-        Ok(fd_iter.map(|fd| fd.unwrap()).collect())
+    fn add_rule(&self, field: RuleField, match_kind: MatchKind, pattern: &str, action: &RuleAction) -> Result<Rule, Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.conn.execute(
+            "INSERT INTO rules (id, field, match_kind, pattern, action) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![id, field.to_string(), match_kind.to_string(), pattern, action.to_string()],
+        )?;
+
+        self.conn
+            .query_row(
+                &format!("SELECT {RULE_COLUMNS} FROM rules WHERE id = ?1"),
+                [&id],
+                rule_from_row,
+            )
+            .map_err(|err| err.into())
     }
-}
 
-use rusqlite_migration::{Migrations, M};
+    fn list_rules(&self) -> Result<Vec<Rule>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RULE_COLUMNS} FROM rules ORDER BY created_at, rowid"
+        ))?;
+        let rule_iter = stmt.query_map([], rule_from_row)?;
 
-const MIGRATIONS_SLICE: &[M<'_>] = &[
-    M::up(
-        "CREATE TABLE feeds (
-            id TEXT PRIMARY KEY,
-            url TEXT NOT NULL UNIQUE,
-            title TEXT,
-            description TEXT,
-            last_synced_at INTEGER,
-            created_at INTEGER NOT NULL DEFAULT (unixepoch()),
-            updated_at INTEGER NOT NULL DEFAULT (unixepoch())
-        );",
-    ),
-    M::up(
-        "CREATE TABLE feed_entries (
-            id TEXT PRIMARY KEY,
-            feed_id TEXT NOT NULL,
-            title TEXT NOT NULL,
-            description TEXT NOT NULL,
-            guid TEXT NOT NULL UNIQUE,
-            created_at INTEGER NOT NULL DEFAULT (unixepoch()),
-            publish_time INTEGER NULL,
-            link VARCHAR(256) NOT NULL,
-            approved INTEGER NOT NULL DEFAULT 1
-        );
-        CREATE INDEX feed_entries_feed_id ON feed_entries (feed_id);
-        CREATE INDEX feed_entries_approved ON feed_entries (approved);",
-    ),
-];
-const MIGRATIONS: Migrations<'_> = Migrations::from_slice(MIGRATIONS_SLICE);
+        Ok(rule_iter.map(|r| r.unwrap()).collect())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn remove_rule(&self, id: &str) -> Result<(), Error> {
+        let deleted = self.conn.execute("DELETE FROM rules WHERE id = ?1", [id])?;
+        if deleted == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
 
-    #[test]
-    fn list_feeds_returns_empty_list() {
-        let store = Store::new_in_memory();
-        let feeds = store.list_feeds().unwrap();
-        assert!(feeds.is_empty());
+    fn tag_entry(&self, entry_id: &str, tag: &str) -> Result<(), Error> {
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO entry_tags (entry_id, tag) VALUES (?1, ?2)",
+                [entry_id, tag],
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(ref err, _)
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Error::NotFound
+                }
+                other => other.into(),
+            })?;
+        Ok(())
     }
 
-    #[test]
-    fn get_feed_returns_not_found() {
-        let store = Store::new_in_memory();
-        let result = store.get_feed("nonexistent-id");
-        assert!(matches!(result, Err(Error::NotFound)));
+    fn add_folder(&self, name: &str) -> Result<Folder, Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.conn
+            .execute("INSERT INTO folders (id, name) VALUES (?1, ?2)", [&id, name])
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(ref err, _)
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Error::AlreadyExists
+                }
+                other => other.into(),
+            })?;
+
+        self.conn
+            .query_row(
+                &format!("SELECT {FOLDER_COLUMNS} FROM folders WHERE id = ?1"),
+                [&id],
+                folder_from_row,
+            )
+            .map_err(|err| err.into())
     }
 
-    #[tokio::test]
-    async fn add_feed_returns_already_exists_for_duplicate_url() {
-        let store = Store::new_in_memory();
-        store
-            .add_feed("https://example.com/rss".into())
-            .await
-            .unwrap();
-        let result = store.add_feed("https://example.com/rss".into()).await;
-        assert!(matches!(result, Err(Error::AlreadyExists)));
+    fn list_folders(&self) -> Result<Vec<Folder>, Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {FOLDER_COLUMNS} FROM folders ORDER BY name"
+        ))?;
+        let folder_iter = stmt.query_map([], folder_from_row)?;
+
+        Ok(folder_iter.map(|f| f.unwrap()).collect())
     }
 
-    #[tokio::test]
-    async fn get_feed_returns_inserted_feed() {
-        let store = Store::new_in_memory();
-        let added = store
-            .add_feed("https://example.com/rss".into())
-            .await
-            .unwrap();
-        let fetched = store.get_feed(&added.id).unwrap();
-        assert_eq!(fetched.id, added.id);
-        assert_eq!(fetched.url, "https://example.com/rss");
+    fn rename_folder(&self, id: &str, name: &str) -> Result<(), Error> {
+        let updated = self
+            .conn
+            .execute("UPDATE folders SET name = ?1 WHERE id = ?2", [name, id])
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(ref err, _)
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Error::AlreadyExists
+                }
+                other => other.into(),
+            })?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
     }
 
-    #[test]
-    fn list_entries_returns_empty_for_unknown_feed() {
-        let store = Store::new_in_memory();
-        let entries = store.list_entries("nonexistent-feed-id", false).unwrap();
-        assert!(entries.is_empty());
+    /// Removes the folder. Its feeds are only taken out of it, through the
+    /// foreign key.
+    fn remove_folder(&self, id: &str) -> Result<(), Error> {
+        let deleted = self.conn.execute("DELETE FROM folders WHERE id = ?1", [id])?;
+        if deleted == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn add_feed_to_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error> {
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO feed_folders (feed_id, folder_id) VALUES (?1, ?2)",
+                [feed_id, folder_id],
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::SqliteFailure(ref err, _)
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+                {
+                    Error::NotFound
+                }
+                other => other.into(),
+            })?;
+        Ok(())
+    }
+
+    fn remove_feed_from_folder(&self, feed_id: &str, folder_id: &str) -> Result<(), Error> {
+        let deleted = self.conn.execute(
+            "DELETE FROM feed_folders WHERE feed_id = ?1 AND folder_id = ?2",
+            [feed_id, folder_id],
+        )?;
+        if deleted == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn move_feed(&self, feed_id: &str, url: &str) -> Result<String, Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let existing: Option<String> = tx
+            .query_row("SELECT id FROM feeds WHERE url = ?1", [url], |row| row.get(0))
+            .optional()?;
+
+        let moved_id = match existing.as_deref() {
+            // The URL is already subscribed to, so the feeds are one and the
+            // same. Entries the other feed already has go with this feed, once
+            // their read and starred state and tags are carried over.
+            Some(other_id) if other_id != feed_id => {
+                tx.execute(
+                    "UPDATE OR IGNORE feed_entries SET feed_id = ?1 WHERE feed_id = ?2",
+                    [other_id, feed_id],
+                )?;
+                tx.execute(
+                    "UPDATE feed_entries SET
+                        read = read OR EXISTS (SELECT 1 FROM feed_entries AS old
+                            WHERE old.feed_id = ?2 AND old.guid = feed_entries.guid AND old.read),
+                        starred = starred OR EXISTS (SELECT 1 FROM feed_entries AS old
+                            WHERE old.feed_id = ?2 AND old.guid = feed_entries.guid AND old.starred)
+                     WHERE feed_id = ?1",
+                    [other_id, feed_id],
+                )?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO entry_tags (entry_id, tag)
+                     SELECT kept.id, entry_tags.tag
+                     FROM entry_tags
+                     JOIN feed_entries AS old ON old.id = entry_tags.entry_id
+                     JOIN feed_entries AS kept ON kept.feed_id = ?1 AND kept.guid = old.guid
+                     WHERE old.feed_id = ?2",
+                    [other_id, feed_id],
+                )?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO feed_folders (feed_id, folder_id)
+                     SELECT ?1, folder_id FROM feed_folders WHERE feed_id = ?2",
+                    [other_id, feed_id],
+                )?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO pruned_entries (feed_id, guid, last_seen_at)
+                     SELECT ?1, guid, COALESCE((SELECT last_fetched_at FROM feeds WHERE id = ?1), 0)
+                     FROM pruned_entries WHERE feed_id = ?2",
+                    [other_id, feed_id],
+                )?;
+                // Settings take the stricter of the two feeds: approval is
+                // required and the feed paused if either says so. The other
+                // feed's own retention policy wins over this one's.
+                tx.execute(
+                    "UPDATE feeds SET
+                        previous_url = old.url,
+                        require_approval = feeds.require_approval OR old.require_approval,
+                        paused = feeds.paused OR old.paused,
+                        retention_max_age_days = CASE WHEN feeds.retention_keep_unread IS NULL
+                            THEN old.retention_max_age_days ELSE feeds.retention_max_age_days END,
+                        retention_max_entries = CASE WHEN feeds.retention_keep_unread IS NULL
+                            THEN old.retention_max_entries ELSE feeds.retention_max_entries END,
+                        retention_keep_unread = COALESCE(feeds.retention_keep_unread, old.retention_keep_unread)
+                     FROM (SELECT * FROM feeds WHERE id = ?2) AS old
+                     WHERE feeds.id = ?1",
+                    [other_id, feed_id],
+                )?;
+                let deleted = tx.execute("DELETE FROM feeds WHERE id = ?1", [feed_id])?;
+                if deleted == 0 {
+                    return Err(Error::NotFound);
+                }
+                other_id.to_string()
+            }
+            _ => {
+                let updated = tx.execute(
+                    "UPDATE feeds SET previous_url = url, url = ?1, updated_at = unixepoch() WHERE id = ?2 AND url != ?1",
+                    [url, feed_id],
+                )?;
+                if updated == 0 && existing.is_none() {
+                    return Err(Error::NotFound);
+                }
+                feed_id.to_string()
+            }
+        };
+        tx.commit()?;
+        Ok(moved_id)
+    }
+
+    /// Removes the feed. Its entries go with it through the foreign key.
+    fn remove_feed(&self, id: &str) -> Result<(), Error> {
+        let deleted = self.conn.execute("DELETE FROM feeds WHERE id = ?1", [id])?;
+        if deleted == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn list_entries_returns_inserted_entries() {
+    fn mark_feed_synced(&self, feed_id: &str) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feeds SET last_synced_at = unixepoch(), next_fetch_at = NULL, consecutive_failures = 0, last_error = NULL
+            WHERE id = ?1",
+            [feed_id],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn get_retention(&self) -> Result<RetentionPolicy, Error> {
+        Ok(self.conn.query_row(
+            "SELECT max_age_days, max_entries, keep_unread FROM retention_policy WHERE id = 1",
+            [],
+            |row| {
+                Ok(RetentionPolicy {
+                    max_age_days: row.get::<_, Option<i64>>(0)?.map(|v| v as u64),
+                    max_entries: row.get::<_, Option<i64>>(1)?.map(|v| v as u64),
+                    keep_unread: row.get::<_, i64>(2)? != 0,
+                })
+            },
+        )?)
+    }
+
+    fn set_retention(&self, policy: &RetentionPolicy) -> Result<(), Error> {
+        self.conn.execute(
+            "UPDATE retention_policy SET max_age_days = ?1, max_entries = ?2, keep_unread = ?3 WHERE id = 1",
+            rusqlite::params![
+                policy.max_age_days.map(|v| v as i64),
+                policy.max_entries.map(|v| v as i64),
+                policy.keep_unread
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn set_feed_retention(&self, feed_id: &str, policy: Option<&RetentionPolicy>) -> Result<(), Error> {
+        let updated = self.conn.execute(
+            "UPDATE feeds SET retention_max_age_days = ?1, retention_max_entries = ?2, retention_keep_unread = ?3
+            WHERE id = ?4",
+            rusqlite::params![
+                policy.and_then(|p| p.max_age_days).map(|v| v as i64),
+                policy.and_then(|p| p.max_entries).map(|v| v as i64),
+                policy.map(|p| p.keep_unread),
+                feed_id
+            ],
+        )?;
+        if updated == 0 {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+
+    fn prune_entries(&self, global: &RetentionPolicy, dry_run: bool) -> Result<Vec<FeedPruneResult>, Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut results = vec![];
+        for feed in self.list_feeds()? {
+            let policy = feed.retention.as_ref().unwrap_or(global);
+            if policy.is_unbounded() {
+                continue;
+            }
+
+            // Entries are counted towards max_entries newest first, whether or
+            // not they are kept for other reasons
+            let mut stmt = tx.prepare_cached(
+                "SELECT id, guid FROM (
+                    SELECT id, guid, approved, read, starred, rejected,
+                        COALESCE(publish_time, created_at) AS sort_time,
+                        ROW_NUMBER() OVER (ORDER BY COALESCE(publish_time, created_at) DESC, id DESC) AS position
+                    FROM feed_entries
+                    WHERE feed_id = ?1
+                )
+                WHERE starred = 0 AND (approved = 1 OR rejected = 1) AND NOT (?4 AND read = 0 AND rejected = 0)
+                  AND (sort_time < unixepoch() - ?2 OR position > ?3)",
+            )?;
+            let pruned = stmt
+                .query_map(
+                    rusqlite::params![
+                        feed.id,
+                        policy.max_age_days.map(|days| days as i64 * 24 * 60 * 60),
+                        policy.max_entries.map(|v| v as i64),
+                        policy.keep_unread
+                    ],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            if pruned.is_empty() {
+                continue;
+            }
+
+            if !dry_run {
+                for (id, guid) in &pruned {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO pruned_entries (feed_id, guid, last_seen_at)
+                         SELECT ?1, ?2, COALESCE(last_fetched_at, 0) FROM feeds WHERE id = ?1",
+                        [&feed.id, guid],
+                    )?;
+                    tx.execute("DELETE FROM feed_entries WHERE id = ?1", [id])?;
+                }
+            }
+            results.push(FeedPruneResult {
+                feed_id: feed.id,
+                url: feed.url,
+                entries: pruned.len(),
+            });
+        }
+
+        // Once a feed no longer lists a pruned entry it can't bring it back,
+        // so its tombstone can go
+        if !dry_run {
+            tx.execute(
+                "DELETE FROM pruned_entries
+                 WHERE last_seen_at < (SELECT last_fetched_at FROM feeds WHERE feeds.id = pruned_entries.feed_id)",
+                [],
+            )?;
+        }
+        tx.commit()?;
+        Ok(results)
+    }
+
+    /// Vacuuming may renumber the rowids entries_fts indexes entries by, so the
+    /// index is rebuilt after.
+    fn vacuum(&self) -> Result<(), Error> {
+        self.conn.execute_batch(
+            "VACUUM;
+            INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');",
+        )?;
+        Ok(())
+    }
+
+    /// Lists all feeds tracked within the store.
+    fn list_feeds(&self) -> Result<Vec<Feed>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {FEED_COLUMNS} FROM feeds;"))?;
+        let fd_iter = stmt.query_map([], feed_from_row)?;
+
+        Ok(fd_iter.map(|fd| fd.unwrap()).collect())
+    }
+}
+
+/// The columns read by feed_from_row, in order. The unread count only covers
+/// approved entries, since those are the only ones shown in the timeline.
+const FEED_COLUMNS: &str =
+    "id, url, title, description, last_synced_at, created_at, updated_at, etag, last_modified, require_approval, previous_url,
+    paused, next_fetch_at, consecutive_failures, last_error,
+    (SELECT COUNT(*) FROM feed_entries fe WHERE fe.feed_id = feeds.id AND fe.approved = 1 AND fe.read = 0),
+    (SELECT group_concat(name, char(31)) FROM (
+        SELECT fo.name FROM feed_folders ff JOIN folders fo ON fo.id = ff.folder_id
+        WHERE ff.feed_id = feeds.id ORDER BY fo.name
+    )),
+    retention_max_age_days, retention_max_entries, retention_keep_unread";
+
+fn feed_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Feed> {
+    Ok(Feed {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        last_synced_at: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+        created_at: row.get::<_, i64>(5)? as u64,
+        updated_at: row.get::<_, i64>(6)? as u64,
+        validators: CacheValidators {
+            etag: row.get(7)?,
+            last_modified: row.get(8)?,
+        },
+        require_approval: row.get::<_, i64>(9)? != 0,
+        previous_url: row.get(10)?,
+        paused: row.get::<_, i64>(11)? != 0,
+        next_fetch_at: row.get::<_, Option<i64>>(12)?.map(|v| v as u64),
+        consecutive_failures: row.get::<_, i64>(13)? as u64,
+        last_error: row.get(14)?,
+        unread_count: row.get::<_, i64>(15)? as u64,
+        folders: split_list(row.get(16)?),
+        // A feed has its own policy when keep_unread is set one way or the other
+        retention: row
+            .get::<_, Option<i64>>(19)?
+            .map(|keep_unread| -> rusqlite::Result<RetentionPolicy> {
+                Ok(RetentionPolicy {
+                    max_age_days: row.get::<_, Option<i64>>(17)?.map(|v| v as u64),
+                    max_entries: row.get::<_, Option<i64>>(18)?.map(|v| v as u64),
+                    keep_unread: keep_unread != 0,
+                })
+            })
+            .transpose()?,
+    })
+}
+
+/// The columns read by folder_from_row, in order.
+const FOLDER_COLUMNS: &str =
+    "id, name, created_at, (SELECT COUNT(*) FROM feed_folders WHERE folder_id = folders.id)";
+
+fn folder_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Folder> {
+    Ok(Folder {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get::<_, i64>(2)? as u64,
+        feed_count: row.get::<_, i64>(3)? as u64,
+    })
+}
+
+/// Splits a list built with group_concat(value, char(31)). The ASCII unit
+/// separator can't reasonably appear in the values.
+fn split_list(joined: Option<String>) -> Vec<String> {
+    joined
+        .map(|joined| joined.split('\u{1f}').map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// The columns read by entry_from_row, in order. Queries select them from
+/// feed_entries aliased as fe.
+const ENTRY_COLUMNS: &str =
+    "fe.id, fe.feed_id, fe.title, fe.description, fe.guid, fe.link, fe.created_at, fe.publish_time, fe.approved, fe.read, fe.starred, fe.rejected, fe.author,
+    (SELECT group_concat(tag, char(31)) FROM (SELECT tag FROM entry_tags WHERE entry_id = fe.id ORDER BY tag)),
+    fe.duplicate_of, fe.updated_at";
+const ENTRY_COLUMN_COUNT: usize = 16;
+
+fn entry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<FeedEntry> {
+    Ok(FeedEntry {
+        id: row.get(0)?,
+        feed_id: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        guid: row.get(4)?,
+        link: row.get(5)?,
+        created_at: row.get::<_, i64>(6)? as u64,
+        publish_time: row.get::<_, Option<i64>>(7)?.map(|v| v as u64),
+        approved: row.get::<_, i64>(8)? != 0,
+        read: row.get::<_, i64>(9)? != 0,
+        starred: row.get::<_, i64>(10)? != 0,
+        rejected: row.get::<_, i64>(11)? != 0,
+        author: row.get(12)?,
+        tags: split_list(row.get(13)?),
+        duplicate_of: row.get(14)?,
+        updated_at: row.get::<_, Option<i64>>(15)?.map(|v| v as u64),
+    })
+}
+
+/// Updates a stored entry that the feed changed, keeping what it was before as
+/// a revision. A feed that stops giving a publish time keeps the one it gave.
+fn revise_entry(conn: &Connection, feed_id: &str, entry: &RemoteEntry) -> rusqlite::Result<()> {
+    let publish_time = entry.publish_time_unix_secs.map(|s| s as i64);
+    let changed: Option<String> = conn
+        .query_row(
+            "SELECT id FROM feed_entries
+             WHERE feed_id = ?1 AND guid = ?2
+               AND (title != ?3 OR description != ?4 OR link != ?5 OR publish_time IS NOT COALESCE(?6, publish_time))",
+            rusqlite::params![feed_id, entry.guid, entry.title, entry.description, entry.link, publish_time],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(id) = changed else {
+        return Ok(());
+    };
+
+    conn.execute(
+        "INSERT INTO entry_revisions (id, entry_id, title, description, link, publish_time)
+         SELECT ?1, id, title, description, link, publish_time FROM feed_entries WHERE id = ?2",
+        [uuid::Uuid::new_v4().to_string(), id.clone()],
+    )?;
+    conn.execute(
+        "UPDATE feed_entries
+         SET title = ?2, description = ?3, link = ?4, publish_time = COALESCE(?5, publish_time), updated_at = unixepoch()
+         WHERE id = ?1",
+        rusqlite::params![id, entry.title, entry.description, entry.link, publish_time],
+    )?;
+    set_dedup_keys(conn, &id, &entry.link, &entry.title)
+}
+
+/// Stores the keys dedup compares an entry by, and points it at the earliest
+/// entry stored before it in another feed that it's a copy of. Entries are only
+/// ever pointed at originals, never at other copies.
+fn set_dedup_keys(conn: &Connection, entry_id: &str, link: &str, title: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE feed_entries SET link_key = ?2, title_key = ?3 WHERE id = ?1",
+        rusqlite::params![entry_id, dedup::link_key(link), dedup::title_key(title)],
+    )?;
+    conn.execute(
+        "UPDATE feed_entries AS fe SET duplicate_of = (
+            SELECT o.id FROM feed_entries o
+            WHERE o.rowid < fe.rowid AND o.feed_id != fe.feed_id AND o.duplicate_of IS NULL
+              AND (o.link_key = fe.link_key
+                OR (o.title_key = fe.title_key
+                  AND abs(COALESCE(o.publish_time, o.created_at) - COALESCE(fe.publish_time, fe.created_at)) <= ?2))
+            ORDER BY o.rowid
+            LIMIT 1
+        )
+        WHERE fe.id = ?1",
+        rusqlite::params![entry_id, dedup::TITLE_WINDOW as i64],
+    )?;
+    Ok(())
+}
+
+/// Splits a timeline cursor into the sort time and ID of the last entry of the
+/// previous page.
+fn parse_cursor(cursor: &str) -> Result<(i64, String), Error> {
+    cursor
+        .split_once(':')
+        .and_then(|(time, id)| Some((time.parse().ok()?, id.to_string())))
+        .ok_or_else(|| Error::InvalidArgument(format!("invalid cursor {cursor:?}")))
+}
+
+/// The columns read by rule_from_row, in order.
+const RULE_COLUMNS: &str = "id, field, match_kind, pattern, action, created_at";
+
+fn rule_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Rule> {
+    // Rules are validated before they're stored, so a value that doesn't parse
+    // means the row was tampered with
+    fn parse<T: std::str::FromStr<Err = Error>>(row: &rusqlite::Row<'_>, idx: usize) -> rusqlite::Result<T> {
+        row.get::<_, String>(idx)?.parse().map_err(|err: Error| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(err))
+        })
+    }
+
+    Ok(Rule {
+        id: row.get(0)?,
+        field: parse(row, 1)?,
+        match_kind: parse(row, 2)?,
+        pattern: row.get(3)?,
+        action: parse(row, 4)?,
+        created_at: row.get::<_, i64>(5)? as u64,
+    })
+}
+
+use rusqlite_migration::{Migrations, M};
+
+fn migrations() -> Migrations<'static> {
+    Migrations::new(vec![
+        M::up(
+            "CREATE TABLE feeds (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL UNIQUE,
+                title TEXT,
+                description TEXT,
+                last_synced_at INTEGER,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+                updated_at INTEGER NOT NULL DEFAULT (unixepoch())
+            );",
+        ),
+        M::up(
+            "CREATE TABLE feed_entries (
+                id TEXT PRIMARY KEY,
+                feed_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                guid TEXT NOT NULL UNIQUE,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+                publish_time INTEGER NULL,
+                link VARCHAR(256) NOT NULL,
+                approved INTEGER NOT NULL DEFAULT 1
+            );
+            CREATE INDEX feed_entries_feed_id ON feed_entries (feed_id);
+            CREATE INDEX feed_entries_approved ON feed_entries (approved);",
+        ),
+        M::up(
+            "ALTER TABLE feeds ADD COLUMN etag TEXT;
+            ALTER TABLE feeds ADD COLUMN last_modified TEXT;",
+        ),
+        // SQLite can't add a foreign key to an existing table, so feed_entries is
+        // rebuilt with one. Entries whose feed no longer exists are dropped.
+        M::up(
+            "CREATE TABLE feed_entries_new (
+                id TEXT PRIMARY KEY,
+                feed_id TEXT NOT NULL REFERENCES feeds (id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                guid TEXT NOT NULL UNIQUE,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+                publish_time INTEGER NULL,
+                link VARCHAR(256) NOT NULL,
+                approved INTEGER NOT NULL DEFAULT 1
+            );
+            INSERT INTO feed_entries_new (id, feed_id, title, description, guid, created_at, publish_time, link, approved)
+                SELECT id, feed_id, title, description, guid, created_at, publish_time, link, approved
+                FROM feed_entries
+                WHERE feed_id IN (SELECT id FROM feeds);
+            DROP TABLE feed_entries;
+            ALTER TABLE feed_entries_new RENAME TO feed_entries;
+            CREATE INDEX feed_entries_feed_id ON feed_entries (feed_id);
+            CREATE INDEX feed_entries_approved ON feed_entries (approved);",
+        ),
+        M::up(
+            "ALTER TABLE feed_entries ADD COLUMN read INTEGER NOT NULL DEFAULT 0;
+            CREATE INDEX feed_entries_read ON feed_entries (read);",
+        ),
+        M::up(
+            "ALTER TABLE feed_entries ADD COLUMN starred INTEGER NOT NULL DEFAULT 0;
+            CREATE INDEX feed_entries_starred ON feed_entries (starred);",
+        ),
+        M::up(
+            "ALTER TABLE feeds ADD COLUMN require_approval INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE feed_entries ADD COLUMN rejected INTEGER NOT NULL DEFAULT 0;",
+        ),
+        M::up(
+            "ALTER TABLE feed_entries ADD COLUMN author TEXT;
+            CREATE TABLE rules (
+                id TEXT PRIMARY KEY,
+                field TEXT NOT NULL,
+                match_kind TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                action TEXT NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch())
+            );
+            CREATE TABLE entry_tags (
+                entry_id TEXT NOT NULL REFERENCES feed_entries (id) ON DELETE CASCADE,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (entry_id, tag)
+            );
+            CREATE INDEX entry_tags_tag ON entry_tags (tag);",
+        ),
+        // entries_fts indexes feed_entries by rowid without copying the text. Anything
+        // that renumbers feed_entries rowids (VACUUM, rebuilding the table) has to be
+        // followed by a 'rebuild' of the index.
+        M::up(
+            "CREATE VIRTUAL TABLE entries_fts USING fts5 (
+                title,
+                description,
+                content = 'feed_entries',
+                content_rowid = 'rowid',
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+            CREATE TRIGGER feed_entries_fts_insert AFTER INSERT ON feed_entries BEGIN
+                INSERT INTO entries_fts (rowid, title, description) VALUES (new.rowid, new.title, new.description);
+            END;
+            CREATE TRIGGER feed_entries_fts_delete AFTER DELETE ON feed_entries BEGIN
+                INSERT INTO entries_fts (entries_fts, rowid, title, description) VALUES ('delete', old.rowid, old.title, old.description);
+            END;
+            CREATE TRIGGER feed_entries_fts_update AFTER UPDATE OF title, description ON feed_entries BEGIN
+                INSERT INTO entries_fts (entries_fts, rowid, title, description) VALUES ('delete', old.rowid, old.title, old.description);
+                INSERT INTO entries_fts (rowid, title, description) VALUES (new.rowid, new.title, new.description);
+            END;
+            INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');",
+        ),
+        M::up("CREATE INDEX feed_entries_timeline ON feed_entries (COALESCE(publish_time, created_at) DESC, id DESC);"),
+        M::up(
+            "CREATE TABLE folders (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch())
+            );
+            CREATE TABLE feed_folders (
+                feed_id TEXT NOT NULL REFERENCES feeds (id) ON DELETE CASCADE,
+                folder_id TEXT NOT NULL REFERENCES folders (id) ON DELETE CASCADE,
+                PRIMARY KEY (feed_id, folder_id)
+            );
+            CREATE INDEX feed_folders_folder ON feed_folders (folder_id);",
+        ),
+        M::up("ALTER TABLE feeds ADD COLUMN previous_url TEXT;"),
+        M::up(
+            "ALTER TABLE feeds ADD COLUMN paused INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE feeds ADD COLUMN next_fetch_at INTEGER;",
+        ),
+        M::up(
+            "ALTER TABLE feeds ADD COLUMN consecutive_failures INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE feeds ADD COLUMN last_error TEXT;",
+        ),
+        // GUIDs only have to be unique within a feed, which means rebuilding
+        // feed_entries. Rowids are kept, but the search index is rebuilt anyway
+        // along with the triggers that went with the old table. The keys dedup
+        // compares entries by are filled in for existing entries afterwards.
+        M::up_with_hook(
+            "CREATE TABLE feed_entries_new (
+                id TEXT PRIMARY KEY,
+                feed_id TEXT NOT NULL REFERENCES feeds (id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                guid TEXT NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+                publish_time INTEGER NULL,
+                link VARCHAR(256) NOT NULL,
+                approved INTEGER NOT NULL DEFAULT 1,
+                read INTEGER NOT NULL DEFAULT 0,
+                starred INTEGER NOT NULL DEFAULT 0,
+                rejected INTEGER NOT NULL DEFAULT 0,
+                author TEXT,
+                link_key TEXT,
+                title_key TEXT,
+                duplicate_of TEXT REFERENCES feed_entries (id) ON DELETE SET NULL,
+                UNIQUE (feed_id, guid)
+            );
+            INSERT INTO feed_entries_new (rowid, id, feed_id, title, description, guid, created_at, publish_time, link, approved, read, starred, rejected, author)
+                SELECT rowid, id, feed_id, title, description, guid, created_at, publish_time, link, approved, read, starred, rejected, author
+                FROM feed_entries;
+            DROP TABLE feed_entries;
+            ALTER TABLE feed_entries_new RENAME TO feed_entries;
+            CREATE INDEX feed_entries_approved ON feed_entries (approved);
+            CREATE INDEX feed_entries_read ON feed_entries (read);
+            CREATE INDEX feed_entries_starred ON feed_entries (starred);
+            CREATE INDEX feed_entries_timeline ON feed_entries (COALESCE(publish_time, created_at) DESC, id DESC);
+            CREATE INDEX feed_entries_link_key ON feed_entries (link_key);
+            CREATE INDEX feed_entries_title_key ON feed_entries (title_key);
+            CREATE INDEX feed_entries_duplicate_of ON feed_entries (duplicate_of);
+            CREATE TRIGGER feed_entries_fts_insert AFTER INSERT ON feed_entries BEGIN
+                INSERT INTO entries_fts (rowid, title, description) VALUES (new.rowid, new.title, new.description);
+            END;
+            CREATE TRIGGER feed_entries_fts_delete AFTER DELETE ON feed_entries BEGIN
+                INSERT INTO entries_fts (entries_fts, rowid, title, description) VALUES ('delete', old.rowid, old.title, old.description);
+            END;
+            CREATE TRIGGER feed_entries_fts_update AFTER UPDATE OF title, description ON feed_entries BEGIN
+                INSERT INTO entries_fts (entries_fts, rowid, title, description) VALUES ('delete', old.rowid, old.title, old.description);
+                INSERT INTO entries_fts (rowid, title, description) VALUES (new.rowid, new.title, new.description);
+            END;
+            INSERT INTO entries_fts (entries_fts) VALUES ('rebuild');",
+            |tx: &rusqlite::Transaction| {
+                let entries = tx
+                    .prepare("SELECT id, link, title FROM feed_entries ORDER BY rowid")?
+                    .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (id, link, title) in entries {
+                    set_dedup_keys(tx, &id, &link, &title)?;
+                }
+                Ok(())
+            },
+        )
+        .foreign_key_check(),
+        M::up(
+            "ALTER TABLE feed_entries ADD COLUMN updated_at INTEGER;
+            CREATE TABLE entry_revisions (
+                id TEXT PRIMARY KEY,
+                entry_id TEXT NOT NULL REFERENCES feed_entries (id) ON DELETE CASCADE,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                link TEXT NOT NULL,
+                publish_time INTEGER,
+                replaced_at INTEGER NOT NULL DEFAULT (unixepoch())
+            );
+            CREATE INDEX entry_revisions_entry ON entry_revisions (entry_id);",
+        ),
+        // The global retention policy is the single row of retention_policy. A
+        // feed with its own policy has retention_keep_unread set. The GUIDs of
+        // pruned entries are kept so that syncing doesn't bring them back.
+        M::up(
+            "CREATE TABLE retention_policy (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                max_age_days INTEGER,
+                max_entries INTEGER,
+                keep_unread INTEGER NOT NULL DEFAULT 0
+            );
+            INSERT INTO retention_policy (id) VALUES (1);
+            ALTER TABLE feeds ADD COLUMN retention_max_age_days INTEGER;
+            ALTER TABLE feeds ADD COLUMN retention_max_entries INTEGER;
+            ALTER TABLE feeds ADD COLUMN retention_keep_unread INTEGER;
+            ALTER TABLE feeds ADD COLUMN last_fetched_at INTEGER;
+            CREATE TABLE pruned_entries (
+                feed_id TEXT NOT NULL REFERENCES feeds (id) ON DELETE CASCADE,
+                guid TEXT NOT NULL,
+                last_seen_at INTEGER NOT NULL,
+                PRIMARY KEY (feed_id, guid)
+            );",
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_creates_the_database_and_keeps_it() {
+        let dir = std::env::temp_dir().join(format!("seymour-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("nested").join("data.sqlite3");
+
+        let feed = Store::open(&path)
+            .unwrap()
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let reopened = Store::open(&path).unwrap();
+        assert_eq!(reopened.get_feed(&feed.id).unwrap().url, "https://example.com/rss");
+        let journal_mode: String = reopened
+            .conn
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+
+        // A directory isn't a database
+        assert!(matches!(Store::open(&dir), Err(Error::Internal(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn list_feeds_returns_empty_list() {
+        let store = Store::new_in_memory();
+        let feeds = store.list_feeds().unwrap();
+        assert!(feeds.is_empty());
+    }
+
+    #[test]
+    fn get_feed_returns_not_found() {
+        let store = Store::new_in_memory();
+        let result = store.get_feed("nonexistent-id");
+        assert!(matches!(result, Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn add_feed_returns_already_exists_for_duplicate_url() {
+        let store = Store::new_in_memory();
+        store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let result = store.add_feed("https://example.com/rss".into());
+        assert!(matches!(result, Err(Error::AlreadyExists)));
+    }
+
+    #[test]
+    fn get_feed_returns_inserted_feed() {
+        let store = Store::new_in_memory();
+        let added = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let fetched = store.get_feed(&added.id).unwrap();
+        assert_eq!(fetched.id, added.id);
+        assert_eq!(fetched.url, "https://example.com/rss");
+    }
+
+    #[test]
+    fn update_feed_stores_cache_validators() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        assert_eq!(feed.validators, CacheValidators::default());
+
+        let remote = RemoteFeed {
+            url: "https://example.com/".into(),
+            title: "Example".into(),
+            description: "An example feed".into(),
+            skipped_entries: vec![],
+            validators: CacheValidators {
+                etag: Some("\"abc123\"".into()),
+                last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".into()),
+            },
+            moved_to: None,
+        };
+        store.update_feed(&feed.id, &remote, &[]).unwrap();
+
+        let fetched = store.get_feed(&feed.id).unwrap();
+        assert_eq!(fetched.validators, remote.validators);
+        assert!(fetched.last_synced_at.is_some());
+    }
+
+    #[test]
+    fn update_feed_returns_new_entry_count() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let remote = RemoteFeed {
+            url: "https://example.com/".into(),
+            title: "Example".into(),
+            description: "An example feed".into(),
+            skipped_entries: vec![],
+            validators: CacheValidators::default(),
+            moved_to: None,
+        };
+        let entry = |guid: &str| RemoteEntry {
+            title: format!("Post {guid}"),
+            description: "".into(),
+            guid: guid.into(),
+            link: format!("https://example.com/{guid}"),
+            publish_time_unix_secs: None,
+            author: None,
+        };
+
+        let inserted = store
+            .update_feed(&feed.id, &remote, &[entry("guid-1"), entry("guid-2")])
+            .unwrap();
+        assert_eq!(inserted.len(), 2);
+
+        // Entries already stored aren't counted again
+        let inserted = store
+            .update_feed(&feed.id, &remote, &[entry("guid-2"), entry("guid-3")])
+            .unwrap();
+        assert_eq!(inserted.len(), 1);
+        assert_eq!(store.get_entry(&inserted[0]).unwrap().guid, "guid-3");
+    }
+
+    #[test]
+    fn update_feed_keeps_validators_when_an_entry_fails() {
+        let store = Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/rss".into()).unwrap();
+        store
+            .conn
+            .execute_batch(
+                "CREATE TRIGGER fail_insert BEFORE INSERT ON feed_entries WHEN NEW.guid = 'guid-2'
+                 BEGIN SELECT RAISE(ABORT, 'insert failed'); END",
+            )
+            .unwrap();
+        let remote = RemoteFeed {
+            url: "https://example.com/".into(),
+            title: "Example".into(),
+            description: "An example feed".into(),
+            skipped_entries: vec![],
+            validators: CacheValidators {
+                etag: Some("\"abc123\"".into()),
+                last_modified: None,
+            },
+            moved_to: None,
+        };
+        let entry = |guid: &str| RemoteEntry {
+            title: format!("Post {guid}"),
+            description: "".into(),
+            guid: guid.into(),
+            link: format!("https://example.com/{guid}"),
+            publish_time_unix_secs: None,
+            author: None,
+        };
+
+        assert!(store
+            .update_feed(&feed.id, &remote, &[entry("guid-1"), entry("guid-2")])
+            .is_err());
+
+        // Nothing of the sync is kept, so the next one fetches the feed in full
+        let fetched = store.get_feed(&feed.id).unwrap();
+        assert_eq!(fetched.validators, CacheValidators::default());
+        assert!(store.list_entries(&feed.id, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn mark_feed_synced_sets_last_synced_at() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        assert!(feed.last_synced_at.is_none());
+
+        store.mark_feed_synced(&feed.id).unwrap();
+
+        let fetched = store.get_feed(&feed.id).unwrap();
+        assert!(fetched.last_synced_at.is_some());
+    }
+
+    #[test]
+    fn failures_count_up_until_a_sync_succeeds() {
+        let store = Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/rss".into()).unwrap();
+        assert_eq!(feed.consecutive_failures, 0);
+
+        store.record_feed_failure(&feed.id, "timed out", Some(100)).unwrap();
+        store.record_feed_failure(&feed.id, "HTTP 502 Bad Gateway", Some(200)).unwrap();
+        let feed = store.get_feed(&feed.id).unwrap();
+        assert_eq!(feed.consecutive_failures, 2);
+        assert_eq!(feed.last_error.as_deref(), Some("HTTP 502 Bad Gateway"));
+        assert_eq!(feed.next_fetch_at, Some(200));
+
+        store.mark_feed_synced(&feed.id).unwrap();
+        let feed = store.get_feed(&feed.id).unwrap();
+        assert_eq!(feed.consecutive_failures, 0);
+        assert_eq!(feed.last_error, None);
+        assert_eq!(feed.next_fetch_at, None);
+
+        assert!(matches!(
+            store.record_feed_failure("nonexistent-id", "timed out", None),
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[test]
+    fn mark_feed_synced_returns_not_found() {
+        let store = Store::new_in_memory();
+        let result = store.mark_feed_synced("nonexistent-id");
+        assert!(matches!(result, Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn remove_feed_deletes_feed_and_entries() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let other = store
+            .add_feed("https://example.com/atom".into())
+            .unwrap();
+        for (id, feed_id) in [("entry-1", &feed.id), ("entry-2", &other.id)] {
+            store.conn.execute(
+                "INSERT INTO feed_entries (id, feed_id, title, description, guid, link) VALUES (?1, ?2, 'Post', '', ?1, 'https://example.com/post')",
+                rusqlite::params![id, feed_id],
+            ).unwrap();
+        }
+
+        store.remove_feed(&feed.id).unwrap();
+
+        assert!(matches!(store.get_feed(&feed.id), Err(Error::NotFound)));
+        assert!(store.list_entries(&feed.id, true).unwrap().is_empty());
+        // Other feeds are untouched
+        assert_eq!(store.list_entries(&other.id, true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn remove_feed_returns_not_found() {
+        let store = Store::new_in_memory();
+        let result = store.remove_feed("nonexistent-id");
+        assert!(matches!(result, Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn migration_drops_orphaned_entries() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations().to_version(&mut conn, 3).unwrap();
+        conn.execute("INSERT INTO feeds (id, url) VALUES ('feed-1', 'https://example.com/rss')", []).unwrap();
+        for (id, feed_id) in [("entry-1", "feed-1"), ("entry-2", "deleted-feed")] {
+            conn.execute(
+                "INSERT INTO feed_entries (id, feed_id, title, description, guid, link) VALUES (?1, ?2, 'Post', '', ?1, 'https://example.com/post')",
+                [id, feed_id],
+            ).unwrap();
+        }
+
+        let store = Store::from_connection(conn).unwrap();
+
+        assert_eq!(store.list_entries("feed-1", true).unwrap().len(), 1);
+        assert!(store.list_entries("deleted-feed", true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn migration_scopes_guids_to_feeds_and_keeps_entries() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations().to_version(&mut conn, 14).unwrap();
+        conn.execute_batch(
+            "INSERT INTO feeds (id, url) VALUES ('feed-1', 'https://example.com/rss'), ('feed-2', 'https://example.com/tech');
+            INSERT INTO feed_entries (id, feed_id, title, description, guid, link, starred)
+                VALUES ('entry-1', 'feed-1', 'Hello world', 'First post', 'guid-1', 'https://example.com/hello', 1);
+            INSERT INTO entry_tags (entry_id, tag) VALUES ('entry-1', 'intro');",
+        )
+        .unwrap();
+
+        let store = Store::from_connection(conn).unwrap();
+
+        let entry = store.get_entry("entry-1").unwrap();
+        assert!(entry.starred);
+        assert_eq!(entry.tags, vec!["intro"]);
+        let found = store.search_entries("hello", &SearchFilters::default()).unwrap();
+        assert_eq!(found.len(), 1);
+
+        // The same GUID can now show up in another feed
+        let remote = RemoteEntry {
+            title: "Hello world".into(),
+            description: "First post".into(),
+            guid: "guid-1".into(),
+            link: "http://www.example.com/hello/".into(),
+            publish_time_unix_secs: None,
+            author: None,
+        };
+        let inserted = store.update_feed("feed-2", &remote_feed(), &[remote]).unwrap();
+        assert_eq!(inserted.len(), 1);
+        assert_eq!(store.get_entry(&inserted[0]).unwrap().duplicate_of.as_deref(), Some("entry-1"));
+        assert_eq!(store.search_entries("hello", &SearchFilters::default()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn changed_entries_are_updated_and_keep_revisions() {
+        let store = Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/rss".into()).unwrap();
+        let original = RemoteEntry {
+            publish_time_unix_secs: Some(1767225600),
+            ..remote_entry("guid-1")
+        };
+        let id = store.update_feed(&feed.id, &remote_feed(), std::slice::from_ref(&original)).unwrap()[0].clone();
+
+        // Syncing the same content again changes nothing
+        assert!(store.update_feed(&feed.id, &remote_feed(), std::slice::from_ref(&original)).unwrap().is_empty());
+        assert!(store.list_revisions(&id).unwrap().is_empty());
+        assert_eq!(store.get_entry(&id).unwrap().updated_at, None);
+
+        let corrected = RemoteEntry {
+            title: "Post guid-1, corrected".into(),
+            description: "Now with fewer typos".into(),
+            ..original.clone()
+        };
+        let undated = RemoteEntry {
+            publish_time_unix_secs: None,
+            ..corrected.clone()
+        };
+        assert!(store.update_feed(&feed.id, &remote_feed(), &[corrected]).unwrap().is_empty());
+        // Losing the publish time isn't a change
+        store.update_feed(&feed.id, &remote_feed(), &[undated]).unwrap();
+
+        let entry = store.get_entry(&id).unwrap();
+        assert_eq!(entry.title, "Post guid-1, corrected");
+        assert_eq!(entry.publish_time, Some(1767225600));
+        assert!(entry.updated_at.is_some());
+        let revisions = store.list_revisions(&id).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].title, "Post guid-1");
+        assert_eq!(revisions[0].publish_time, Some(1767225600));
+        assert_eq!(store.search_entries("typos", &SearchFilters::default()).unwrap().len(), 1);
+
+        assert!(matches!(store.list_revisions("nonexistent-id"), Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn pruning_follows_retention_and_sticks() {
+        let store = Store::new_in_memory();
+        let feed = store.add_feed("https://example.com/rss".into()).unwrap();
+        let now = crate::unix_now();
+        // Entry k was published k days and an hour ago
+        let entries: Vec<RemoteEntry> = (1..=5)
+            .map(|k| RemoteEntry {
+                publish_time_unix_secs: Some(now - k * 24 * 60 * 60 - 60 * 60),
+                ..remote_entry(&format!("guid-{k}"))
+            })
+            .collect();
+        let ids = store.update_feed(&feed.id, &remote_feed(), &entries).unwrap();
+        store.set_entry_starred(&ids[4], true).unwrap();
+        let remaining = |store: &Store| -> Vec<String> {
+            store.list_entries(&feed.id, true).unwrap().into_iter().map(|e| e.guid).collect()
+        };
+
+        // Nothing is pruned until there's a policy
+        assert_eq!(store.get_retention().unwrap(), RetentionPolicy::default());
+        assert!(store.prune_entries(&store.get_retention().unwrap(), false).unwrap().is_empty());
+
+        // Only the newest three are kept, and starred entries
+        let global = RetentionPolicy {
+            max_entries: Some(3),
+            ..Default::default()
+        };
+        store.set_retention(&global).unwrap();
+        assert_eq!(store.get_retention().unwrap(), global);
+        assert_eq!(store.prune_entries(&global, true).unwrap()[0].entries, 1);
+        assert_eq!(remaining(&store).len(), 5);
+        assert_eq!(store.prune_entries(&global, false).unwrap()[0].entries, 1);
+        assert!(!remaining(&store).contains(&"guid-4".to_string()));
+
+        // The feed's own policy wins, and can keep unread entries
+        let own = RetentionPolicy {
+            max_age_days: Some(2),
+            max_entries: None,
+            keep_unread: true,
+        };
+        store.set_feed_retention(&feed.id, Some(&own)).unwrap();
+        assert_eq!(store.get_feed(&feed.id).unwrap().retention, Some(own));
+        store.set_entry_read(&ids[2], true).unwrap();
+        assert_eq!(store.prune_entries(&global, false).unwrap()[0].entries, 1);
+        assert_eq!(remaining(&store), vec!["guid-1", "guid-2", "guid-5"]);
+
+        // Pruned entries don't come back on the next sync
+        assert!(store.update_feed(&feed.id, &remote_feed(), &entries).unwrap().is_empty());
+        assert_eq!(remaining(&store).len(), 3);
+
+        // Tombstones go once the feed stops listing their GUIDs
+        let tombstones = |store: &Store| -> Vec<String> {
+            let mut stmt = store.conn.prepare("SELECT guid FROM pruned_entries ORDER BY guid").unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().map(|guid| guid.unwrap()).collect()
+        };
+        assert_eq!(tombstones(&store), vec!["guid-3", "guid-4"]);
+        // As if the last sync was a minute ago
+        store
+            .conn
+            .execute_batch(
+                "UPDATE feeds SET last_fetched_at = last_fetched_at - 60;
+                UPDATE pruned_entries SET last_seen_at = last_seen_at - 60;",
+            )
+            .unwrap();
+        let without_4: Vec<RemoteEntry> = entries.iter().filter(|e| e.guid != "guid-4").cloned().collect();
+        store.update_feed(&feed.id, &remote_feed(), &without_4).unwrap();
+        store.prune_entries(&global, true).unwrap();
+        assert_eq!(tombstones(&store), vec!["guid-3", "guid-4"]);
+        store.prune_entries(&global, false).unwrap();
+        assert_eq!(tombstones(&store), vec!["guid-3"]);
+        // Should guid-4 turn up again, it's a new entry
+        assert_eq!(store.update_feed(&feed.id, &remote_feed(), &entries).unwrap().len(), 1);
+
+        store.vacuum().unwrap();
+        assert_eq!(store.search_entries("guid", &SearchFilters::default()).unwrap().len(), 4);
+        assert!(matches!(store.set_feed_retention("nonexistent-id", None), Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn duplicates_across_feeds_collapse_in_the_timeline() {
+        let store = Store::new_in_memory();
+        let main = store.add_feed("https://example.com/rss".into()).unwrap();
+        let category = store.add_feed("https://example.com/category/rust/rss".into()).unwrap();
+        let other = store.add_feed("https://example.org/rss".into()).unwrap();
+        let entry = |guid: &str, title: &str, link: &str, publish_time: u64| RemoteEntry {
+            title: title.into(),
+            description: String::new(),
+            guid: guid.into(),
+            link: link.into(),
+            publish_time_unix_secs: Some(publish_time),
+            author: None,
+        };
+
+        store
+            .update_feed(&main.id, &remote_feed(), &[
+                entry("main-1", "Announcing 1.0", "https://example.com/1.0", 1767225600),
+                entry("main-2", "Weekly notes", "https://example.com/notes-1", 1767225600),
+            ])
+            .unwrap();
+        let copies = store
+            .update_feed(&category.id, &remote_feed(), &[
+                // The same GUID as in the main feed isn't dropped any more
+                entry("main-1", "Announcing 1.0", "https://example.com/1.0#more", 1767225600),
+            ])
+            .unwrap();
+        let others = store
+            .update_feed(&other.id, &remote_feed(), &[
+                // Same title, published the same day, elsewhere
+                entry("other-1", "Announcing 1.0!", "https://example.org/news", 1767240000),
+                // Same title, but weeks later
+                entry("other-2", "Weekly notes", "https://example.org/notes", 1769904000),
+            ])
+            .unwrap();
+
+        let duplicate_of = |id: &str| store.get_entry(id).unwrap().duplicate_of;
+        let original = store.list_entries(&main.id, true).unwrap();
+        let original = original.iter().find(|e| e.guid == "main-1").unwrap();
+        assert_eq!(duplicate_of(&copies[0]).as_ref(), Some(&original.id));
+        assert_eq!(duplicate_of(&others[0]).as_ref(), Some(&original.id));
+        assert_eq!(duplicate_of(&others[1]), None);
+
+        let timeline = |query: TimelineQuery| store.list_timeline(&query).unwrap().entries.len();
+        assert_eq!(timeline(TimelineQuery::default()), 5);
+        let collapsed = TimelineQuery {
+            collapse_duplicates: true,
+            ..Default::default()
+        };
+        assert_eq!(timeline(collapsed.clone()), 3);
+        // Copies are kept when the original isn't part of the timeline
+        assert_eq!(
+            timeline(TimelineQuery {
+                feed_ids: vec![category.id.clone()],
+                ..collapsed
+            }),
+            1
+        );
+    }
+
+    fn insert_entry(store: &Store, id: &str, feed_id: &str, publish_time: i64) {
+        store.conn.execute(
+            "INSERT INTO feed_entries (id, feed_id, title, description, guid, link, publish_time) VALUES (?1, ?2, ?1, '', ?1, 'https://example.com/post', ?3)",
+            rusqlite::params![id, feed_id, publish_time],
+        ).unwrap();
+    }
+
+    #[test]
+    fn read_state_is_tracked_per_entry_and_feed() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let other = store
+            .add_feed("https://example.com/atom".into())
+            .unwrap();
+        insert_entry(&store, "entry-1", &feed.id, 1767312000);
+        insert_entry(&store, "entry-2", &feed.id, 1767398400);
+        insert_entry(&store, "entry-3", &other.id, 1767484800);
+
+        assert_eq!(store.get_feed(&feed.id).unwrap().unread_count, 2);
+        let unread_only = TimelineQuery {
+            read: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(store.list_timeline(&unread_only).unwrap().entries.len(), 3);
+
+        store.set_entry_read("entry-1", true).unwrap();
+        assert_eq!(store.get_feed(&feed.id).unwrap().unread_count, 1);
+        let unread: Vec<String> = store
+            .list_timeline(&unread_only)
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|(_, e)| e.id)
+            .collect();
+        assert_eq!(unread, vec!["entry-3", "entry-2"]);
+        // Read entries are still in the full timeline
+        assert_eq!(store.list_timeline(&TimelineQuery::default()).unwrap().entries.len(), 3);
+
+        assert_eq!(store.set_feed_read(&feed.id, true).unwrap(), 1);
+        assert_eq!(store.get_feed(&feed.id).unwrap().unread_count, 0);
+        assert_eq!(store.get_feed(&other.id).unwrap().unread_count, 1);
+
+        assert_eq!(store.set_feed_read(&feed.id, false).unwrap(), 2);
+        assert_eq!(store.mark_all_read().unwrap(), 3);
+        assert!(store.list_timeline(&unread_only).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn timeline_pages_with_cursor() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let other = store
+            .add_feed("https://example.com/atom".into())
+            .unwrap();
+        insert_entry(&store, "entry-1", &feed.id, 1767312000);
+        // Two entries published at the same time are told apart by ID
+        insert_entry(&store, "entry-2", &feed.id, 1767398400);
+        insert_entry(&store, "entry-3", &feed.id, 1767398400);
+        insert_entry(&store, "entry-4", &other.id, 1767484800);
+
+        let mut query = TimelineQuery {
+            limit: 2,
+            ..Default::default()
+        };
+        let mut ids = vec![];
+        loop {
+            let page = store.list_timeline(&query).unwrap();
+            assert!(page.entries.len() <= 2);
+            ids.extend(page.entries.into_iter().map(|(_, e)| e.id));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(ids, vec!["entry-4", "entry-3", "entry-2", "entry-1"]);
+
+        let filtered = store
+            .list_timeline(&TimelineQuery {
+                feed_ids: vec![feed.id.clone()],
+                since: Some(1767398400),
+                ..Default::default()
+            })
+            .unwrap();
+        let ids: Vec<_> = filtered.entries.into_iter().map(|(_, e)| e.id).collect();
+        assert_eq!(ids, vec!["entry-3", "entry-2"]);
+        assert!(filtered.next_cursor.is_none());
+
+        assert!(matches!(
+            store.list_timeline(&TimelineQuery {
+                cursor: Some("garbage".into()),
+                ..Default::default()
+            }),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn move_feed_updates_url() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("http://example.com/rss".into())
+            .unwrap();
+
+        let moved_id = store.move_feed(&feed.id, "https://example.com/rss").unwrap();
+
+        assert_eq!(moved_id, feed.id);
+        let feed = store.get_feed(&feed.id).unwrap();
+        assert_eq!(feed.url, "https://example.com/rss");
+        assert_eq!(feed.previous_url.as_deref(), Some("http://example.com/rss"));
+        assert!(matches!(
+            store.move_feed("nonexistent-id", "https://example.com/other"),
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[test]
+    fn move_feed_merges_into_existing_feed() {
+        let store = Store::new_in_memory();
+        let old = store
+            .add_feed("http://example.com/rss".into())
+            .unwrap();
+        let current = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        insert_entry(&store, "entry-1", &old.id, 1767312000);
+        insert_entry(&store, "entry-2", &current.id, 1767398400);
+        // Both feeds have entry-2; the copy left behind was read and starred
+        store.conn.execute(
+            "INSERT INTO feed_entries (id, feed_id, title, description, guid, link, read, starred)
+             VALUES ('entry-2-old', ?1, 'entry-2', '', 'entry-2', 'https://example.com/post', 1, 1)",
+            [&old.id],
+        ).unwrap();
+        store.tag_entry("entry-2-old", "security").unwrap();
+        let folder = store.add_folder("Tech").unwrap();
+        store.add_feed_to_folder(&old.id, &folder.id).unwrap();
+        store.set_feed_require_approval(&old.id, true).unwrap();
+        store.set_feed_paused(&old.id, true).unwrap();
+        let policy = RetentionPolicy {
+            max_entries: Some(50),
+            ..Default::default()
+        };
+        store.set_feed_retention(&old.id, Some(&policy)).unwrap();
+
+        let moved_id = store.move_feed(&old.id, "https://example.com/rss").unwrap();
+
+        assert_eq!(moved_id, current.id);
+        assert!(matches!(store.get_feed(&old.id), Err(Error::NotFound)));
+        let merged = store.get_feed(&current.id).unwrap();
+        assert_eq!(merged.previous_url.as_deref(), Some("http://example.com/rss"));
+        assert_eq!(merged.folders, vec!["Tech"]);
+        assert!(merged.require_approval && merged.paused);
+        assert_eq!(merged.retention, Some(policy));
+        let entries = store.list_entries(&current.id, true).unwrap();
+        assert_eq!(entries.len(), 2);
+        let kept = entries.iter().find(|e| e.id == "entry-2").unwrap();
+        assert!(kept.read && kept.starred);
+        assert_eq!(kept.tags, vec!["security"]);
+    }
+
+    #[test]
+    fn folders_group_feeds() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let other = store
+            .add_feed("https://example.com/atom".into())
+            .unwrap();
+        insert_entry(&store, "entry-1", &feed.id, 1767312000);
+        insert_entry(&store, "entry-2", &other.id, 1767398400);
+
+        let tech = store.add_folder("Tech").unwrap();
+        let news = store.add_folder("News").unwrap();
+        assert!(matches!(store.add_folder("tech"), Err(Error::AlreadyExists)));
+
+        store.add_feed_to_folder(&feed.id, &tech.id).unwrap();
+        store.add_feed_to_folder(&feed.id, &news.id).unwrap();
+        // Adding twice is a no-op
+        store.add_feed_to_folder(&feed.id, &news.id).unwrap();
+        store.add_feed_to_folder(&other.id, &news.id).unwrap();
+        assert!(matches!(
+            store.add_feed_to_folder("nonexistent-id", &news.id),
+            Err(Error::NotFound)
+        ));
+
+        assert_eq!(store.get_feed(&feed.id).unwrap().folders, vec!["News", "Tech"]);
+        let folders = store.list_folders().unwrap();
+        let counts: Vec<_> = folders.iter().map(|f| (f.name.as_str(), f.feed_count)).collect();
+        assert_eq!(counts, vec![("News", 2), ("Tech", 1)]);
+
+        let in_tech = store
+            .list_timeline(&TimelineQuery {
+                folder_id: Some(tech.id.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+        let ids: Vec<_> = in_tech.entries.into_iter().map(|(_, e)| e.id).collect();
+        assert_eq!(ids, vec!["entry-1"]);
+
+        store.rename_folder(&tech.id, "Technology").unwrap();
+        assert!(matches!(store.rename_folder(&tech.id, "news"), Err(Error::AlreadyExists)));
+        store.remove_feed_from_folder(&feed.id, &news.id).unwrap();
+        assert!(matches!(
+            store.remove_feed_from_folder(&feed.id, &news.id),
+            Err(Error::NotFound)
+        ));
+        assert_eq!(store.get_feed(&feed.id).unwrap().folders, vec!["Technology"]);
+
+        // Removing a folder keeps its feeds
+        store.remove_folder(&tech.id).unwrap();
+        assert!(store.get_feed(&feed.id).unwrap().folders.is_empty());
+        assert!(matches!(store.remove_folder(&tech.id), Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn starred_entries_are_listed() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        insert_entry(&store, "entry-1", &feed.id, 1767312000);
+        insert_entry(&store, "entry-2", &feed.id, 1767398400);
+        insert_entry(&store, "entry-3", &feed.id, 1767484800);
+        assert!(store.list_starred().unwrap().is_empty());
+
+        store.set_entry_starred("entry-1", true).unwrap();
+        store.set_entry_starred("entry-3", true).unwrap();
+        let starred: Vec<String> = store
+            .list_starred()
+            .unwrap()
+            .into_iter()
+            .map(|(_, e)| e.id)
+            .collect();
+        assert_eq!(starred, vec!["entry-3", "entry-1"]);
+
+        store.set_entry_starred("entry-3", false).unwrap();
+        let starred = store.list_starred().unwrap();
+        assert_eq!(starred.len(), 1);
+        assert!(starred[0].1.starred);
+        assert_eq!(starred[0].0, "https://example.com/rss");
+    }
+
+    #[test]
+    fn set_entry_starred_returns_not_found() {
+        let store = Store::new_in_memory();
+        let result = store.set_entry_starred("nonexistent-id", true);
+        assert!(matches!(result, Err(Error::NotFound)));
+    }
+
+    fn remote_feed() -> RemoteFeed {
+        RemoteFeed {
+            url: "https://example.com/".into(),
+            title: "Example".into(),
+            description: "An example feed".into(),
+            skipped_entries: vec![],
+            validators: CacheValidators::default(),
+            moved_to: None,
+        }
+    }
+
+    fn remote_entry(guid: &str) -> RemoteEntry {
+        RemoteEntry {
+            title: format!("Post {guid}"),
+            description: "".into(),
+            guid: guid.into(),
+            link: format!("https://example.com/{guid}"),
+            publish_time_unix_secs: None,
+            author: None,
+        }
+    }
+
+    #[test]
+    fn search_ranks_and_filters_entries() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        let other = store
+            .add_feed("https://example.com/atom".into())
+            .unwrap();
+        let entry = |guid: &str, title: &str, description: &str, publish_time: u64| RemoteEntry {
+            title: title.into(),
+            description: description.into(),
+            publish_time_unix_secs: Some(publish_time),
+            ..remote_entry(guid)
+        };
+        store
+            .update_feed(
+                &feed.id,
+                &remote_feed(),
+                &[
+                    entry("a", "Notes on caching", "Why the café cache was slow", 1767225600),
+                    entry("b", "Cache invalidation", "The second hard problem", 1768003200),
+                    entry("c", "Gardening", "Nothing to see here", 1768003200),
+                ],
+            )
+            .unwrap();
+        store
+            .update_feed(&other.id, &remote_feed(), &[entry("d", "More cache talk", "", 1768003200)])
+            .unwrap();
+
+        let results = store.search_entries("cache", &SearchFilters::default()).unwrap();
+        let guids: Vec<_> = results.iter().map(|r| r.entry.guid.as_str()).collect();
+        // Title matches rank above the description match
+        assert_eq!(guids.len(), 3);
+        assert_eq!(guids[2], "a");
+        assert_eq!(results[2].snippet, "Why the café [cache] was slow");
+
+        // Diacritics are folded
+        let results = store.search_entries("cafe", &SearchFilters::default()).unwrap();
+        assert_eq!(results.len(), 1);
+
+        let filters = SearchFilters {
+            feed_id: Some(feed.id.clone()),
+            since: Some(1767312000),
+            ..Default::default()
+        };
+        let results = store.search_entries("cache", &filters).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].entry.guid, "b");
+
+        assert!(matches!(
+            store.search_entries("\"cache", &SearchFilters::default()),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn search_index_follows_entry_changes() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        store
+            .update_feed(&feed.id, &remote_feed(), &[remote_entry("guid-1")])
+            .unwrap();
+        assert_eq!(store.search_entries("post", &SearchFilters::default()).unwrap().len(), 1);
+
+        store
+            .conn
+            .execute("UPDATE feed_entries SET title = 'Renamed'", [])
+            .unwrap();
+        assert_eq!(store.search_entries("post", &SearchFilters::default()).unwrap().len(), 0);
+        assert_eq!(store.search_entries("renamed", &SearchFilters::default()).unwrap().len(), 1);
+
+        store.remove_feed(&feed.id).unwrap();
+        assert_eq!(store.search_entries("renamed", &SearchFilters::default()).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn feeds_requiring_approval_queue_new_entries() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        store
+            .update_feed(&feed.id, &remote_feed(), &[remote_entry("guid-1")])
+            .unwrap();
+
+        store.set_feed_require_approval(&feed.id, true).unwrap();
+        assert!(store.get_feed(&feed.id).unwrap().require_approval);
+        store
+            .update_feed(
+                &feed.id,
+                &remote_feed(),
+                &[remote_entry("guid-1"), remote_entry("guid-2"), remote_entry("guid-3")],
+            )
+            .unwrap();
+
+        // Only the entries that arrived after approval was required are pending
+        let pending = store.list_pending(Some(&feed.id)).unwrap();
+        assert_eq!(pending.len(), 2);
+        assert!(pending.iter().all(|(_, e)| !e.approved));
+        assert_eq!(store.list_entries(&feed.id, false).unwrap().len(), 1);
+        assert_eq!(store.list_pending(Some("other-feed")).unwrap().len(), 0);
+
+        let (approve, reject) = (pending[0].1.id.clone(), pending[1].1.id.clone());
+        // An ID given twice counts once
+        assert_eq!(store.set_entries_approved(&[approve.clone(), approve], true).unwrap(), 1);
+        assert_eq!(store.set_entries_approved(std::slice::from_ref(&reject), false).unwrap(), 1);
+
+        assert!(store.list_pending(None).unwrap().is_empty());
+        assert_eq!(store.list_entries(&feed.id, false).unwrap().len(), 2);
+        let rejected: Vec<FeedEntry> = store
+            .list_entries(&feed.id, true)
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.rejected)
+            .collect();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].id, reject);
+
+        // A rejected entry isn't brought back by the next sync
+        store
+            .update_feed(&feed.id, &remote_feed(), &[remote_entry("guid-3")])
+            .unwrap();
+        assert!(store.list_pending(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn set_entries_approved_is_all_or_nothing() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        store.set_feed_require_approval(&feed.id, true).unwrap();
+        store
+            .update_feed(&feed.id, &remote_feed(), &[remote_entry("guid-1")])
+            .unwrap();
+        let pending = store.list_pending(None).unwrap();
+
+        let result = store.set_entries_approved(
+            &[pending[0].1.id.clone(), "nonexistent-id".into()],
+            true,
+        );
+
+        assert!(matches!(result, Err(Error::NotFound)));
+        assert_eq!(store.list_pending(None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rules_are_stored_in_order() {
+        let store = Store::new_in_memory();
+        let first = store
+            .add_rule(RuleField::Title, MatchKind::Substring, "sponsored", &RuleAction::Reject)
+            .unwrap();
+        let second = store
+            .add_rule(
+                RuleField::Title,
+                MatchKind::Keywords,
+                "security advisory",
+                &RuleAction::Tag("security".into()),
+            )
+            .unwrap();
+
+        let rules = store.list_rules().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].id, first.id);
+        assert_eq!(rules[1].id, second.id);
+        assert_eq!(rules[1].match_kind, MatchKind::Keywords);
+        assert_eq!(rules[1].action, RuleAction::Tag("security".into()));
+
+        store.remove_rule(&first.id).unwrap();
+        assert_eq!(store.list_rules().unwrap().len(), 1);
+        assert!(matches!(store.remove_rule(&first.id), Err(Error::NotFound)));
+    }
+
+    #[test]
+    fn tags_are_loaded_with_entries() {
+        let store = Store::new_in_memory();
+        let feed = store
+            .add_feed("https://example.com/rss".into())
+            .unwrap();
+        insert_entry(&store, "entry-1", &feed.id, 1767312000);
+
+        store.tag_entry("entry-1", "security").unwrap();
+        store.tag_entry("entry-1", "advisory").unwrap();
+        // Tagging twice is a no-op
+        store.tag_entry("entry-1", "security").unwrap();
+
+        let entry = store.get_entry("entry-1").unwrap();
+        assert_eq!(entry.tags, vec!["advisory", "security"]);
+        assert!(matches!(
+            store.tag_entry("nonexistent-id", "security"),
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[test]
+    fn set_read_returns_not_found() {
+        let store = Store::new_in_memory();
+        assert!(matches!(
+            store.set_entry_read("nonexistent-id", true),
+            Err(Error::NotFound)
+        ));
+        assert!(matches!(
+            store.set_feed_read("nonexistent-id", true),
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[test]
+    fn list_entries_returns_empty_for_unknown_feed() {
+        let store = Store::new_in_memory();
+        let entries = store.list_entries("nonexistent-feed-id", false).unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn list_entries_returns_inserted_entries() {
         let store = Store::new_in_memory();
         let feed = store
             .add_feed("https://example.com/rss".into())
-            .await
             .unwrap();
         store.conn.execute(
             "INSERT INTO feed_entries (id, feed_id, title, description, guid, link, publish_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",